@@ -2,6 +2,7 @@
 import { onError,onInfo } from "./utils.js"
 
 var eventSource;
+var countdown;
 
 export function createEventSource() {
 	eventSource = new EventSource('/sse');
@@ -42,16 +43,27 @@ function onEventMessage(ev) {
 	onError("");
 	onInfo("");
 
-	document.getElementById("image").innerHTML = "";
-
 	var data = JSON.parse(ev.data);
 	console.log("Received event: " + JSON.stringify(data));
+	if (data.Buzzer) {
+		onBuzzer(data.Buzzer);
+		return;
+	}
+
+	document.getElementById("image").innerHTML = "";
+	clearInterval(countdown);
+	document.getElementById("timer").innerHTML = "";
+
 	if (data.Lobby) {
 		onLobby(data.Lobby);
 	} else if (data.Question) {
 		onQuestion(data.Question);
+	} else if (data.Wager) {
+		onWager(data.Wager);
 	} else if (data.Ranking) {
 		onRanking(data.Ranking);
+	} else if (data.Distribution) {
+		onDistribution(data.Distribution);
 	} else if (data == "Finished") {
 		onFinished();
 	} else if (data == "Closed") {
@@ -76,23 +88,93 @@ function onLobby(lobby) {
 		table.appendChild(tr);
 	}
 	document.getElementById("main_frame").appendChild(table);
+	if (lobby.teams) {
+		onTeams(lobby.teams);
+	}
+}
+
+function onTeams(teams) {
+	var table = document.createElement('table');
+	var tr = document.createElement('tr');
+	tr.innerHTML = "<tr><th>Teams</th><th>Members</th></tr>";
+	table.appendChild(tr);
+	for (var [team,members] of teams) {
+		var tr = document.createElement('tr');
+		tr.innerHTML = "<td>" + team + "</td><td>" + members.join(", ") + "</td>";
+		table.appendChild(tr);
+	}
+	document.getElementById("main_frame").appendChild(table);
+	var input = document.createElement("input");
+	input.type = "text";
+	input.id = "team";
+	input.placeholder = "team";
+	document.getElementById("main_frame").appendChild(input);
+	var join = document.createElement("button");
+	join.innerHTML = "Join team";
+	join.onclick = function() {
+		joinTeam(document.getElementById("team").value);
+	}
+	document.getElementById("main_frame").appendChild(join);
+}
+
+function joinTeam(team) {
+	var username = window.localStorage.getItem('Quiz_username');
+	if (username == null || team == "") {
+		return
+	}
+	var xmlHttp = new XMLHttpRequest();
+    xmlHttp.onreadystatechange = function() {
+        if (xmlHttp.readyState == 4) {
+			if (xmlHttp.status == 202) {
+				onInfo("Joined team: " + xmlHttp.responseText);
+			} else {
+				onError(xmlHttp.responseText);
+			}
+		}
+    }
+    xmlHttp.open("POST", "/join_team");
+    xmlHttp.send(JSON.stringify({ user: username, team: team }));
 }
 
 function onQuestion(question) {
+	if (question.shuffled) {
+		// the options are shuffled per user, get our own order
+		fetchLatestEvent();
+		return;
+	}
 	document.getElementById("main_frame").innerHTML = "";
 	document.getElementById("sub_title").innerHTML = question.title;
 	document.getElementById("q_nr").innerHTML = (question.id+1) + "/" + question.total;
+	if (question.round) {
+		document.getElementById("q_nr").innerHTML += " - Round " + (question.round.id+1) + "/" + question.round.total + ": " + question.round.name;
+		if (question.round.intro) {
+			onInfo(question.round.intro);
+		}
+	}
+	if (question.deadline) {
+		startCountdown(question.deadline);
+	}
 	if (question.image) {
 		var img = document.createElement("img");
 		img.src = question.image;
 		document.getElementById("image").appendChild(img);
 	}
-	if (question.question_type.MultiChoice) {
-		onMultiChoice(question.title, question.question_type.MultiChoice);
+	if (question.buzzer) {
+		onBuzzerQuestion();
+	} else if (question.question_type.MultiChoice) {
+		onMultiChoice(question.question_id, question.question_type.MultiChoice, "MultiChoice");
+	} else if (question.question_type.Poll) {
+		onMultiChoice(question.question_id, question.question_type.Poll, "Poll");
 	} else if (question.question_type.MultiOption) {
-		onMultiOption(question.title, question.question_type.MultiOption);
+		onMultiOption(question.question_id, question.question_type.MultiOption);
+	} else if (question.question_type.Ordering) {
+		onOrdering(question.question_id, question.question_type.Ordering);
+	} else if (question.question_type.Matching) {
+		onMatching(question.question_id, question.question_type.Matching);
+	} else if (typeof question.question_type == "object" && "Numeric" in question.question_type) {
+		onNumeric(question.question_id, question.question_type.Numeric);
 	} else if (question.question_type == "Open") {
-		onOpen(question.title);
+		onOpen(question.question_id);
 	} else {
 		console.log("Unknown question: ");
 		console.log(question);
@@ -101,7 +183,103 @@ function onQuestion(question) {
 	}
 }
 
-function onMultiChoice(title, multichoice) {
+function onBuzzerQuestion() {
+	var buzz = document.createElement("button");
+	buzz.id = "buzz";
+	buzz.innerHTML = "BUZZ!";
+	buzz.onclick = function() {
+		var username = window.localStorage.getItem('Quiz_username');
+		if (username == null) {
+			return
+		}
+		var xmlHttp = new XMLHttpRequest();
+		xmlHttp.onreadystatechange = function() {
+			if (xmlHttp.readyState == 4) {
+				if (xmlHttp.status == 202) {
+					onInfo("Buzzed!");
+					document.getElementById("buzz").disabled = true;
+				} else {
+					onError(xmlHttp.responseText);
+				}
+			}
+		}
+		xmlHttp.open("POST", "/buzz");
+		xmlHttp.send(username);
+	}
+	document.getElementById("main_frame").appendChild(buzz);
+}
+
+function onBuzzer(buzzer) {
+	var buzz = document.getElementById("buzz");
+	if (buzzer.accepted) {
+		onInfo(buzzer.accepted + " answered correctly!");
+		if (buzz) {
+			buzz.disabled = true;
+		}
+	} else if (buzzer.turn) {
+		onInfo(buzzer.turn + " has the turn to answer.");
+	} else {
+		onInfo("Nobody has the turn, buzz to answer!");
+	}
+}
+
+function onWager(wager) {
+	document.getElementById("main_frame").innerHTML = "";
+	document.getElementById("sub_title").innerHTML = "Wager part of your score on the next question!";
+	document.getElementById("q_nr").innerHTML = (wager.id+1) + "/" + wager.total;
+	if (wager.round) {
+		document.getElementById("q_nr").innerHTML += " - Round " + (wager.round.id+1) + "/" + wager.round.total + ": " + wager.round.name;
+	}
+	var input = document.createElement("input");
+	input.type = "number";
+	input.min = 0;
+	input.step = "any";
+	input.id = "wager";
+	input.placeholder = "Wager";
+	document.getElementById("main_frame").appendChild(input);
+	var submit = document.createElement("button");
+	submit.innerHTML = "Wager";
+	submit.onclick = function() {
+		var input = document.getElementById("wager");
+		if (input.value != "") {
+			placeWager(Number(input.value));
+		}
+	}
+	document.getElementById("main_frame").appendChild(submit);
+}
+
+function placeWager(wager) {
+	var username = window.localStorage.getItem('Quiz_username');
+	if (username == null) {
+		return
+	}
+	var xmlHttp = new XMLHttpRequest();
+    xmlHttp.onreadystatechange = function() {
+        if (xmlHttp.readyState == 4) {
+			if (xmlHttp.status == 202) {
+				onInfo("Placed wager: " + xmlHttp.responseText);
+			} else {
+				onError(xmlHttp.responseText);
+			}
+		}
+    }
+    xmlHttp.open("POST", "/wager");
+    xmlHttp.send(JSON.stringify({ user: username, wager: wager }));
+}
+
+function startCountdown(deadline) {
+	var update = function() {
+		var seconds = Math.max(0, Math.ceil((deadline - Date.now()) / 1000));
+		document.getElementById("timer").innerHTML = seconds + "s";
+		if (seconds == 0) {
+			clearInterval(countdown);
+		}
+	}
+	update();
+	countdown = setInterval(update, 1000);
+}
+
+function onMultiChoice(questionId, multichoice, type) {
 	for (const [index,option] of multichoice.entries()) {
 		var input = document.createElement("input");
 		input.type = "radio";
@@ -116,7 +294,7 @@ function onMultiChoice(title, multichoice) {
 	submit.onclick = function() {
 		for (var input of document.getElementsByName("question")) {
 			if (input.checked) {
-				submitAnswer(title, { "MultiChoice": Number(input.value) });
+				submitAnswer(questionId, { [type]: Number(input.value) });
 				return;
 			}
 		}
@@ -124,7 +302,7 @@ function onMultiChoice(title, multichoice) {
 	document.getElementById("main_frame").appendChild(submit);
 }
 
-function onMultiOption(title, multioption) {
+function onMultiOption(questionId, multioption) {
 	for (const [index,option] of multioption.entries()) {
 		var input = document.createElement("input");
 		input.type = "checkbox";
@@ -144,35 +322,126 @@ function onMultiOption(title, multioption) {
 			}
 		}
 		if (answers.length) {
-			submitAnswer(title, { "MultiOption": answers });
+			submitAnswer(questionId, { "MultiOption": answers });
+		}
+	}
+	document.getElementById("main_frame").appendChild(submit);
+}
+
+function onOrdering(questionId, ordering) {
+	var list = document.createElement("ol");
+	list.id = "ordering";
+	for (const [index,option] of ordering.entries()) {
+		var item = document.createElement("li");
+		item.dataset.index = index;
+		var up = document.createElement("button");
+		up.innerHTML = "&uarr;";
+		up.onclick = function() {
+			var item = this.parentElement;
+			if (item.previousElementSibling) {
+				item.parentElement.insertBefore(item, item.previousElementSibling);
+			}
+		}
+		item.appendChild(up);
+		item.appendChild(document.createTextNode(" " + option));
+		list.appendChild(item);
+	}
+	document.getElementById("main_frame").appendChild(list);
+	var submit = document.createElement("button");
+	submit.innerHTML = "Submit";
+	submit.onclick = function() {
+		var order = [];
+		for (var item of document.getElementById("ordering").children) {
+			order.push(Number(item.dataset.index));
+		}
+		submitAnswer(questionId, { "Ordering": order });
+	}
+	document.getElementById("main_frame").appendChild(submit);
+}
+
+function onMatching(questionId, matching) {
+	var table = document.createElement("table");
+	for (const [index,left] of matching.left.entries()) {
+		var tr = document.createElement("tr");
+		var td = document.createElement("td");
+		td.innerHTML = left;
+		tr.appendChild(td);
+		var select = document.createElement("select");
+		select.name = "matching";
+		for (const [value,right] of matching.right.entries()) {
+			var option = document.createElement("option");
+			option.value = value;
+			option.innerHTML = right;
+			select.appendChild(option);
+		}
+		select.selectedIndex = -1;
+		td = document.createElement("td");
+		td.appendChild(select);
+		tr.appendChild(td);
+		table.appendChild(tr);
+	}
+	document.getElementById("main_frame").appendChild(table);
+	var submit = document.createElement("button");
+	submit.innerHTML = "Submit";
+	submit.onclick = function() {
+		var pairs = [];
+		for (var select of document.getElementsByName("matching")) {
+			if (select.selectedIndex < 0) {
+				onError("Match all items first.");
+				return;
+			}
+			pairs.push(Number(select.value));
+		}
+		submitAnswer(questionId, { "Matching": pairs });
+	}
+	document.getElementById("main_frame").appendChild(submit);
+}
+
+function onNumeric(questionId, unit) {
+	var input = document.createElement("input");
+	input.type = "number";
+	input.step = "any";
+	input.id = questionId;
+	input.placeholder = "Answer";
+	document.getElementById("main_frame").appendChild(input);
+	if (unit) {
+		document.getElementById("main_frame").innerHTML += " " + unit;
+	}
+	document.getElementById("main_frame").innerHTML += "</br>"
+	var submit = document.createElement("button");
+	submit.innerHTML = "Submit";
+	submit.onclick = function() {
+		var input = document.getElementById(questionId);
+		if (input.value != "") {
+			submitAnswer(questionId, { "Numeric": Number(input.value) });
 		}
 	}
 	document.getElementById("main_frame").appendChild(submit);
 }
 
-function onOpen(title) {
+function onOpen(questionId) {
 	var textareaObj = document.createElement("textarea");
-	textareaObj.id = title;
+	textareaObj.id = questionId;
 	textareaObj.placeholder = "Answer";
 	document.getElementById("main_frame").appendChild(textareaObj);
 	document.getElementById("main_frame").innerHTML += "</br>"
 	var submit = document.createElement("button");
 	submit.innerHTML = "Submit";
 	submit.onclick = function() {
-		var textareaObj = document.getElementById(title);
+		var textareaObj = document.getElementById(questionId);
 		if (textareaObj.value != "") {
-			submitAnswer(title, { "Open": textareaObj.value });
+			submitAnswer(questionId, { "Open": textareaObj.value });
 		}
 	}
 	document.getElementById("main_frame").appendChild(submit);
 }
 
-function submitAnswer(title, answer) {
+function submitAnswer(questionId, answer) {
 	var username = window.localStorage.getItem('Quiz_username');
 	if (username == null) {
 		return
 	}
-	let answerObject = { user: username, question: title, answer: answer };
+	let answerObject = { user: username, question_id: questionId, answer: answer };
 	
 	var xmlHttp = new XMLHttpRequest();
     xmlHttp.onreadystatechange = function() {
@@ -193,9 +462,28 @@ function onRanking(ranking) {
 	document.getElementById("sub_title").innerHTML = "Ranking";
 	document.getElementById("q_nr").innerHTML = "";
 	document.getElementById("main_frame").innerHTML = "";
+	for (var round of ranking.rounds) {
+		document.getElementById("main_frame").appendChild(rankingTable(round.name, round));
+	}
+	document.getElementById("main_frame").appendChild(rankingTable("Total", ranking));
+	if (ranking.teams.length) {
+		var table = document.createElement('table');
+		var tr = document.createElement('tr');
+		tr.innerHTML = "<tr><th></th><th>Team</th><th>Score</th></tr>";
+		table.appendChild(tr);
+		for (var [index,[team,score]] of ranking.teams.entries()) {
+			var tr = document.createElement('tr');
+			tr.innerHTML = "<td>" + (index+1) + ".</td><td>" + team + "</td><td>" + score + "</td>";
+			table.appendChild(tr);
+		}
+		document.getElementById("main_frame").appendChild(table);
+	}
+}
+
+function rankingTable(heading, ranking) {
 	var table = document.createElement('table');
 	var tr = document.createElement('tr');
-	tr.innerHTML = "<tr><th></th><th>User</th><th>Score</th></tr>";
+	tr.innerHTML = "<tr><th></th><th>" + heading + "</th><th>Score</th></tr>";
 	table.appendChild(tr);
 	for (var [index,score] of ranking.scores.entries()) {
 		var tr = document.createElement('tr');
@@ -208,6 +496,21 @@ function onRanking(ranking) {
 		}
 		table.appendChild(tr);
 	}
+	return table;
+}
+
+function onDistribution(distribution) {
+	document.getElementById("sub_title").innerHTML = distribution.title;
+	document.getElementById("q_nr").innerHTML = "";
+	document.getElementById("main_frame").innerHTML = "";
+	var total = Math.max(1, distribution.counts.reduce((sum, [_,count]) => sum + count, 0));
+	var table = document.createElement('table');
+	for (var [option,count] of distribution.counts) {
+		var tr = document.createElement('tr');
+		tr.innerHTML = "<td>" + option + "</td><td>" + count + "</td>"
+			+ "<td><div class=\"bar\" style=\"width:" + (count * 200 / total) + "px\"></div></td>";
+		table.appendChild(tr);
+	}
 	document.getElementById("main_frame").appendChild(table);
 }
 
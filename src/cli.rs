@@ -27,6 +27,8 @@ enum QuizCommand {
     Status,
     /// Prints the list of users and their bonus scores.
     Users,
+    /// Prints the list of teams and their members.
+    Teams,
     /// Removes selected user.
     RemoveUser {
         /// User to remove.
@@ -41,6 +43,10 @@ enum QuizCommand {
     Next,
     /// Locks the current question and prevents users from submitting answers. (To unlock again, use `redo`)
     Lock,
+    /// Accepts the answer of the user who has the turn on a buzzer question.
+    Accept,
+    /// Rejects the answer of the user who has the turn on a buzzer question, the next user in line gets the turn.
+    Reject,
     /// Redo a question. Give a question id or use the current question.
     Redo{
         /// Id of the question to redo.
@@ -55,6 +61,14 @@ enum QuizCommand {
         /// Id of the question to summarize.
         id: Option<usize>
     },
+    /// Answer distribution of a choice or poll question. Give a question id or use the current question.
+    Poll{
+        /// Id of the question to show the distribution of.
+        id: Option<usize>,
+        /// Share the distribution to all users.
+        #[arg(long)]
+        share: bool,
+    },
     /// Grade question. Give a question id or grade all with ungraded answers.
     Grade{
         /// Id of the question to grade. Grading all with ungraded answers if none given.
@@ -67,7 +81,7 @@ enum QuizCommand {
         user: String,
         /// Bonus score
         #[arg(allow_negative_numbers=true)]
-        bonus: i32,
+        bonus: f64,
     },
     /// Backup the current state of the quiz.
     Backup{
@@ -75,6 +89,18 @@ enum QuizCommand {
         #[arg(default_value_t = String::from(".backup_quiz"), value_parser=NonEmptyStringValueParser::new())]
         file: String
     },
+    /// Export the answers and scores of all users as CSV and JSON.
+    Export{
+        /// File to write to, only one format is written if it ends in .csv or .json.
+        #[arg(default_value_t = String::from("results"), value_parser=NonEmptyStringValueParser::new())]
+        file: String
+    },
+    /// Write an html report with the ranking and a summary of every question.
+    Report{
+        /// File to write the report to.
+        #[arg(default_value_t = String::from("report.html"), value_parser=NonEmptyStringValueParser::new())]
+        file: String
+    },
     /// Import a backup state of a quiz.
     Import{
         /// File to read backup from.
@@ -88,7 +114,7 @@ pub async fn start(state: QuizStateService, sse: SseService) {
         quiz_command_prefix!();
         let mut input = String::new();
         BufReader::new(io::stdin()).read_line(&mut input).await.expect("Did not enter a correct string");
-        let quiz_args = QuizArgs::try_parse_from(input.trim().split_whitespace());
+        let quiz_args = QuizArgs::try_parse_from(input.split_whitespace());
         let quiz_args = match quiz_args {
             Ok(args) =>  args,
             Err(e) => { println!("{}",e); continue; }
@@ -99,17 +125,23 @@ pub async fn start(state: QuizStateService, sse: SseService) {
             QuizCommand::Status             => command::status(state.clone()).await,
             QuizCommand::Questions          => command::questions(state.clone()).await,
             QuizCommand::Users              => command::users(state.clone()).await,
+            QuizCommand::Teams              => command::teams(state.clone()).await,
             QuizCommand::RemoveUser { user } => command::remove_user(state.clone(), user).await,
             QuizCommand::Start              => command::start_event(state.clone(),sse.clone()).await,
             QuizCommand::Next               => command::next(state.clone(),sse.clone()).await,
             QuizCommand::Lock               => command::lock_question(state.clone()).await,
+            QuizCommand::Accept             => command::accept_buzz(state.clone(),sse.clone()).await,
+            QuizCommand::Reject             => command::reject_buzz(state.clone(),sse.clone()).await,
             QuizCommand::Redo { id } => command::redo_question(state.clone(),sse.clone(), id).await,
             QuizCommand::Ranking            => command::ranking(state.clone()).await,
             QuizCommand::Share              => command::share_ranking(state.clone(),sse.clone()).await,
             QuizCommand::Qsumm { id } => command::qsumm(state.clone(), id, false).await,
+            QuizCommand::Poll { id, share } => command::poll(state.clone(), sse.clone(), id, share).await,
             QuizCommand::Grade { id } => command::grade(state.clone(), id).await,
             QuizCommand::Bonus { user, bonus} => command::add_bonus(state.clone(), user, bonus).await,
             QuizCommand::Backup { file } => command::backup(state.clone(), file).await,
+            QuizCommand::Export { file } => command::export(state.clone(), file).await,
+            QuizCommand::Report { file } => command::report(state.clone(), file).await,
             QuizCommand::Import { file } => command::import_backup(state.clone(),sse.clone(), file).await,
         }
     }
@@ -1,7 +1,9 @@
+use std::path::PathBuf;
+
 use tabular::{Row, Table};
 use tokio::io::{self, AsyncBufReadExt, BufReader};
 
-use crate::{server::SseService, state::{Event, QuizStateService, QuizStatus, Ranking, Score}};
+use crate::{error::QuizResult, server::SseService, state::{Distribution, Event, QuizStateService, QuizStatus, Ranking, Results, RoundRanking, Score}};
 
 async fn yes_no_question(message: &str) -> bool {
     loop {
@@ -27,9 +29,16 @@ pub async fn status(state: QuizStateService) {
     match state.status().await {
         QuizStatus::Lobby => println!("In Lobby"),
         QuizStatus::Done => println!("Finished Quiz"),
+        QuizStatus::Wager{id} => {
+            let question = state.question(id).await.unwrap();
+            println!("Wagering on question\nid: {}\nquestion: {}", id, &question.title());
+        },
         QuizStatus::Question{id,locked} => {
             let question = state.question(id).await.unwrap();
             println!("Question in progress\nid: {}\nquestion: {}", id, &question.title());
+            if let Some(round) = state.round_of(id).await {
+                println!("round: {}", round);
+            }
             if locked {
                 println!("LOCKED");
             }
@@ -57,6 +66,19 @@ pub async fn users(state: QuizStateService) {
     println!("{}", table);
 }
 
+pub async fn teams(state: QuizStateService) {
+    let Some(teams) = state.teams().await else {
+        println!("This quiz is not played in teams.");
+        return;
+    };
+    let mut table = Table::new("\t{:<} {:<}");
+    table.add_row(Row::new().with_cell("Teams").with_cell("Members"));
+    for (team, members) in teams {
+        table.add_row(Row::new().with_cell(format!("`{}`", team)).with_cell(members.join(", ")));
+    }
+    println!("{}", table);
+}
+
 pub async fn remove_user(state: QuizStateService, mut user: String) {
     unquote(&mut user);
     match state.remove_user(&user).await {
@@ -89,9 +111,11 @@ pub async fn continue_on_all_answered(state: QuizStateService) -> bool {
     if no_answer_users.is_empty() {
         true
     } else {
-        if state.status().await.question().is_some() {
+        let status = state.status().await;
+        if status.question().is_some() || status.is_wager() {
             let mut table = Table::new("\t{:<}");
-            table.add_heading("Not all users have answered yet:");
+            table.add_heading(if status.is_wager() { "Not all users have wagered yet:" } 
+                              else { "Not all users have answered yet:" });
             for user in no_answer_users {
                 table.add_row(Row::new().with_cell(user));
             }
@@ -122,6 +146,30 @@ pub async fn lock_question(state: QuizStateService) {
     state.lock_question().await;
 }
 
+pub async fn accept_buzz(state: QuizStateService, sse: SseService) {
+    match state.accept_buzz().await {
+        Some((user, e)) => {
+            println!("Accepted the answer of `{}`.", user);
+            sse.send_event(e).await;
+        },
+        None => println!("Nobody has the turn to answer."),
+    }
+}
+
+pub async fn reject_buzz(state: QuizStateService, sse: SseService) {
+    match state.reject_buzz().await {
+        Some((user, e)) => {
+            println!("Rejected the answer of `{}`.", user);
+            match &e {
+                Event::Buzzer{turn:Some(next),..} => println!("`{}` has the turn now.", next),
+                _ => println!("Nobody else is in line, waiting for someone to buzz."),
+            }
+            sse.send_event(e).await;
+        },
+        None => println!("Nobody has the turn to answer."),
+    }
+}
+
 pub async fn redo_question(state: QuizStateService, sse: SseService, id: Option<usize>) {
     let index = match id {
         Some(index) => index,
@@ -150,16 +198,57 @@ pub async fn share_ranking(state: QuizStateService, sse: SseService) {
     }
 }
 
-pub async fn ranking(state: QuizStateService) {
-    let Ranking{max_score,scores} = state.ranking().await;
-    let mut table = Table::new("\t{:<}: {:>}/{:<}");
+fn score_table(heading: &str, max_score: f64, scores: Vec<(String,f64)>) -> Table {
+    let mut table = Table::new("\t{:<}: {:>}/{:<}").with_heading(heading);
     for (user,score) in scores {
         table.add_row(Row::new()
             .with_cell(user)
             .with_cell(score)
             .with_cell(max_score));
     }
+    table
+}
+
+pub async fn ranking(state: QuizStateService) {
+    let Ranking{max_score,scores,rounds,teams} = state.ranking().await;
+    for RoundRanking{name,max_score,scores} in rounds {
+        println!("{}", score_table(&format!("Round: {}", name), max_score, scores));
+    }
+    println!("{}", score_table("Total:", max_score, scores));
+    if !teams.is_empty() {
+        let mut table = Table::new("\t{:<}: {:>}").with_heading("Teams:");
+        for (team,score) in teams {
+            table.add_row(Row::new().with_cell(team).with_cell(score));
+        }
+        println!("{}", table);
+    }
+}
+
+pub async fn poll(state: QuizStateService, sse: SseService, id: Option<usize>, share: bool) {
+    let index = match id {
+        Some(index) => index,
+        None => if let Some(index) = state.status().await.question() { index } 
+                else { return }
+    };
+
+    let Some(distribution) = state.distribution(index).await else {
+        println!("Question {} has no options to show a distribution of.", index);
+        return;
+    };
+    let Distribution{title,counts} = &distribution;
+    let total = counts.iter().map(|(_,c)| c).sum::<usize>().max(1);
+    let mut table = Table::new("\t{:<} {:>} {:<}")
+        .with_heading(format!("question: {}", title));
+    for (option,count) in counts {
+        table.add_row(Row::new()
+            .with_cell(option)
+            .with_cell(count)
+            .with_cell("#".repeat(count * 40 / total)));
+    }
     println!("{}", table);
+    if share {
+        sse.send_event(Event::Distribution(distribution)).await;
+    }
 }
 
 pub async fn grade(state: QuizStateService, id: Option<usize>) {
@@ -178,7 +267,7 @@ pub async fn grade(state: QuizStateService, id: Option<usize>) {
 }
 
 pub async fn qsumm(state: QuizStateService, id: Option<usize>, do_grade: bool) {
-    assert!(!do_grade || !id.is_none());
+    assert!(!do_grade || id.is_some());
 
     let index = match id {
         Some(index) => index,
@@ -187,28 +276,48 @@ pub async fn qsumm(state: QuizStateService, id: Option<usize>, do_grade: bool) {
     };
 
     let question = state.question(index).await;
+    if do_grade && question.as_ref().is_some_and(|q| !q.is_scored()) {
+        println!("Question {} is not scored.", index);
+        return;
+    }
     let answers = state.answers(index).await;
+    let teams = state.teams().await.unwrap_or_default();
+    let label = |user: &String| match teams.iter().find(|(_,members)| members.contains(user)) {
+        Some((team,_)) => format!("{} ({})", user, team),
+        None => user.clone(),
+    };
     if let (Some(question),Some((answers,score_range))) = (question, answers) {
         let title = question.title();
         let type_ = question.type_spec();
-        let table_head = Table::new("\t{:<}\t{:^} {:>}")
+        let mut table_head = Table::new("\t{:<}\t{:^} {:>} {:>}")
             .with_heading(format!("question: {}", title))
-            .with_heading(format!("type: {}", type_))
+            .with_heading(format!("type: {}", type_));
+        if !question.accept().is_empty() {
+            let accepted = question.accept().answers().iter()
+                .chain(question.accept().patterns())
+                .cloned()
+                .collect::<Vec<_>>();
+            table_head.add_heading(format!("accepted: {}", accepted.join(", ")));
+        }
+        let table_head = table_head
             .with_row(Row::new().with_cell("")
                 .with_cell("Answer")
+                .with_cell("Time")
                 .with_cell("Grade"));
         let mut table = table_head.clone();
-        for (user, (answer,score)) in answers {
+        for (user, (answer,score,time)) in answers {
             let row_head = Row::new()
-                    .with_cell(user.clone())
-                    .with_cell(answer.clone().replace("\n"," "));
+                    .with_cell(label(&user))
+                    .with_cell(answer.clone().replace("\n"," "))
+                    .with_cell(time.map(|t| format!("{:.1}s", t)).unwrap_or_default());
             let mut row = row_head.clone().with_cell(match score {
                         Score::Grade(s) => format!("{}/{}",s,score_range.end()),
+                        Score::Ungraded if question.is_near_miss(&answer) => "not graded yet (probably correct)".to_string(),
                         Score::Ungraded => "not graded yet".to_string(),
                     });
             if do_grade {
                 println!("{}", table_head.clone().with_row(row.clone()));
-                if let Some(new_grade) = grade_answer(state.clone(), &user, &title, score_range.clone()).await {
+                if let Some(new_grade) = grade_answer(state.clone(), &user, question.id(), score_range.clone()).await {
                     row = row_head.with_cell(format!("{}/{}",new_grade,score_range.end()))
                 }
             }
@@ -218,10 +327,10 @@ pub async fn qsumm(state: QuizStateService, id: Option<usize>, do_grade: bool) {
     }
 }
 
-async fn grade_answer(state: QuizStateService,user: &String, question: &String, range: std::ops::RangeInclusive<usize>) -> Option<usize> {
+async fn grade_answer(state: QuizStateService,user: &str, question_id: &str, range: std::ops::RangeInclusive<f64>) -> Option<f64> {
     loop {
         use std::io::Write;
-        print!("Grade (range: {},...,{} or `skip`)> ",range.start(),range.end());
+        print!("Grade (range: {} to {} or `skip`)> ",range.start(),range.end());
         std::io::stdout().flush().expect("Output flush failed");
         let mut s = String::new();
         BufReader::new(io::stdin()).read_line(&mut s).await.expect("Did not enter a correct string");
@@ -230,7 +339,7 @@ async fn grade_answer(state: QuizStateService,user: &String, question: &String,
             return None;
         }
 
-        let result = s.parse::<usize>()
+        let result = s.parse::<f64>()
             .map_err(|e| e.to_string())
             .and_then(|s|{
                 if range.contains(&s) { Ok(s) }
@@ -238,7 +347,7 @@ async fn grade_answer(state: QuizStateService,user: &String, question: &String,
             });
         match result {
             Ok(s) => {
-                state.update_grade(user, question, s).await;
+                state.update_grade(user, question_id, s).await;
                 return Some(s);
             },
             Err(e) => println!("{}",e),
@@ -246,7 +355,7 @@ async fn grade_answer(state: QuizStateService,user: &String, question: &String,
     }
 }
 
-pub async fn add_bonus(state: QuizStateService, mut user: String, bonus: i32) {
+pub async fn add_bonus(state: QuizStateService, mut user: String, bonus: f64) {
     unquote(&mut user);
     match state.add_bonus(&user, bonus).await {
         Ok(_) => println!("Bonus was added successfully"),
@@ -255,15 +364,85 @@ pub async fn add_bonus(state: QuizStateService, mut user: String, bonus: i32) {
 }
 
 pub async fn backup(state: QuizStateService, file: String) {
-    let path = file.into();
+    let path: PathBuf = file.into();
     match state.backup(&path).await {
         Ok(_) => println!("Backup created: {:?}", path),
         Err(e) => println!("An error occurred while trying to backup: {}", e),
     }
 }
 
+pub async fn export(state: QuizStateService, file: String) {
+    let path: PathBuf = file.into();
+    let paths = match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") | Some("json") => vec![path],
+        _ => vec![path.with_extension("csv"), path.with_extension("json")],
+    };
+    let results = state.results().await;
+    for path in paths {
+        let written = if path.extension().is_some_and(|e| e == "csv") {
+            write_results_csv(&results, &path)
+        } else {
+            write_results_json(&results, &path)
+        };
+        match written {
+            Ok(_) => println!("Results exported: {:?}", path),
+            Err(e) => println!("An error occurred while trying to export results: {}", e),
+        }
+    }
+}
+
+fn write_results_json(results: &Results, path: &PathBuf) -> QuizResult<()> {
+    let json = serde_json::to_string_pretty(results)?;
+    Ok(std::fs::write(path, json)?)
+}
+
+fn write_results_csv(results: &Results, path: &PathBuf) -> QuizResult<()> {
+    let teams = results.users.iter().any(|u| u.team.is_some());
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = vec!["rank".to_string(), "user".to_string()];
+    if teams {
+        header.push("team".into());
+    }
+    for question in &results.questions {
+        header.push(format!("{} answer", question.id));
+        header.push(format!("{} grade", question.id));
+    }
+    header.extend(["bonus".to_string(), "total".to_string()]);
+    writer.write_record(&header)?;
+
+    for user in &results.users {
+        let mut record = vec![user.rank.to_string(), user.user.clone()];
+        if teams {
+            record.push(user.team.clone().unwrap_or_default());
+        }
+        for answer in &user.answers {
+            match answer {
+                Some(a) => {
+                    record.push(a.answer.clone());
+                    record.push(match a.grade {
+                        Score::Grade(g) => g.to_string(),
+                        Score::Ungraded => "ungraded".into(),
+                    });
+                },
+                None => record.extend([String::new(), String::new()]),
+            }
+        }
+        record.extend([user.bonus.to_string(), user.total.to_string()]);
+        writer.write_record(&record)?;
+    }
+    Ok(writer.flush()?)
+}
+
+pub async fn report(state: QuizStateService, file: String) {
+    let path: PathBuf = file.into();
+    match std::fs::write(&path, state.report().await) {
+        Ok(_) => println!("Report written: {:?}", path),
+        Err(e) => println!("An error occurred while trying to write the report: {}", e),
+    }
+}
+
 pub async fn import_backup(state: QuizStateService, sse: SseService, file: String) {
-    let path = file.into();
+    let path: PathBuf = file.into();
     match state.import_backup(&path).await {
         Ok(ev) => { 
             println!("Succesfully imported: {:?}", path);
@@ -0,0 +1,127 @@
+mod gift;
+mod markdown;
+mod spreadsheet;
+mod trivia;
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::state::{Config, ConfigFormat};
+
+/// Format of a file with questions to convert.
+#[derive(Copy, Clone, Debug)]
+pub enum SourceFormat {
+    /// A quiz config in json, yaml or toml, or a quiz root with one.
+    Config,
+    Markdown,
+    Csv,
+    Gift,
+    /// A json dump of the Open Trivia DB api.
+    OpenTrivia,
+}
+
+impl FromStr for SourceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "config" => Ok(SourceFormat::Config),
+            "md" | "markdown" => Ok(SourceFormat::Markdown),
+            "csv" => Ok(SourceFormat::Csv),
+            "gift" => Ok(SourceFormat::Gift),
+            "opentdb" | "trivia" => Ok(SourceFormat::OpenTrivia),
+            _ => Err(format!("Unknown source format: {} (use config, markdown, csv, gift or opentdb)", s)),
+        }
+    }
+}
+
+impl SourceFormat {
+    /// The format of a file by its extension, a directory is a quiz root.
+    /// Json files are Open Trivia DB dumps when they look like one.
+    fn of(path: &Path) -> Option<Self> {
+        if ConfigFormat::of(path) == Some(ConfigFormat::Json)
+            && std::fs::read_to_string(path).is_ok_and(|data| trivia::is_dump(&data))
+        {
+            return Some(SourceFormat::OpenTrivia);
+        }
+        if path.is_dir() || path.extension().is_some_and(|e| e == "config") || ConfigFormat::of(path).is_some() {
+            return Some(SourceFormat::Config);
+        }
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+/// Format to convert questions into.
+#[derive(Copy, Clone, Debug)]
+pub enum TargetFormat {
+    /// A quiz.config in json.
+    Config,
+    Gift,
+}
+
+impl FromStr for TargetFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "config" | "json" => Ok(TargetFormat::Config),
+            "gift" => Ok(TargetFormat::Gift),
+            _ => Err(format!("Unknown target format: {} (use config or gift)", s)),
+        }
+    }
+}
+
+impl TargetFormat {
+    fn of(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension == "gift" => TargetFormat::Gift,
+            _ => TargetFormat::Config,
+        }
+    }
+}
+
+/// Reads the questions in `input`, with the questions that were left out.
+fn read(input: &Path, format: SourceFormat) -> Result<(Config, Vec<String>), String> {
+    if let SourceFormat::Config = format {
+        let config = if input.is_dir() { Config::from(input, None) } else { Config::from_file(input) };
+        return config.map(|config| (config, Vec::new())).map_err(|e| format!("Could not read quiz config: {}", e));
+    }
+    let data = std::fs::read_to_string(input)
+        .map_err(|e| format!("Could not read {:?}: {}", input, e))?;
+    // quizzes in formats without a title are named after the file
+    let title = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    match format {
+        SourceFormat::Config => unreachable!(),
+        SourceFormat::Markdown => markdown::parse(&data).map(|config| (config, Vec::new())),
+        SourceFormat::Csv => spreadsheet::parse(&data, title).map(|config| (config, Vec::new())),
+        SourceFormat::Gift => gift::parse(&data, title),
+        SourceFormat::OpenTrivia => trivia::parse(&data, title).map(|config| (config, Vec::new())),
+    }
+}
+
+/// Converts the questions in `input` and writes them to `output`, by default a quiz.config next to the input.
+/// Returns the path written to and the questions that could not be converted, or only in part.
+pub fn convert(input: &Path, output: Option<&Path>, from: Option<SourceFormat>, to: Option<TargetFormat>, force: bool) -> Result<(PathBuf, Vec<String>), String> {
+    let from = from.or_else(|| SourceFormat::of(input))
+        .ok_or_else(|| format!("Unknown format of {:?}, give it with --from", input))?;
+    let (config, mut skipped) = read(input, from)?;
+
+    let to = to.or(output.map(TargetFormat::of)).unwrap_or(TargetFormat::Config);
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| match to {
+        TargetFormat::Config => input.with_file_name("quiz.config"),
+        TargetFormat::Gift => input.with_extension("gift"),
+    });
+    if output.exists() && !force {
+        return Err(format!("{:?} already exists, use --force to overwrite it", output));
+    }
+    let data = match to {
+        TargetFormat::Config => serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?,
+        TargetFormat::Gift => {
+            let (data, lost) = gift::write(&config);
+            skipped.extend(lost);
+            data
+        },
+    };
+    std::fs::write(&output, data).map_err(|e| format!("Could not write {:?}: {}", output, e))?;
+    Ok((output, skipped))
+}
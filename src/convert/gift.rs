@@ -0,0 +1,463 @@
+//! Question banks in the Moodle GIFT format:
+//!
+//! ```text
+//! $CATEGORY: geography
+//!
+//! ::capital:: Which city is the capital of France? {=Paris ~Lyon ~Marseille}
+//! ::planets:: Which are planets? {~%50%Mars ~%-100%Moon ~%50%Venus}
+//! ::rings:: Name a planet with rings. {=Saturn =Jupiter}
+//! ::eiffel:: How many meters tall is the Eiffel tower? {#330:15}
+//! ```
+//!
+//! Multiple choice questions become multi-choice questions, or multi-option questions when the options
+//! are weighted. Short answers become open questions with accepted answers, numerical questions numeric
+//! questions and matching questions matching questions. Question names are used as question ids and
+//! categories as the categories of the questions.
+
+use std::collections::HashMap;
+
+use crate::state::{AcceptedAnswers, Config, OptionScoring, Question, QuestionType, Tolerance};
+
+const SPECIAL: [char; 6] = ['~', '=', '#', '{', '}', ':'];
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if SPECIAL.contains(&c) || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(if c == '\n' { ' ' } else { c });
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Byte positions of the characters in `text` that are not escaped with a backslash.
+fn unescaped(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut escaped = false;
+    text.char_indices().filter(move |(_,c)| {
+        let special = !escaped;
+        escaped = !escaped && *c == '\\';
+        special
+    })
+}
+
+fn find_unescaped(text: &str, pattern: char) -> Option<usize> {
+    unescaped(text).find(|(_,c)| *c == pattern).map(|(i,_)| i)
+}
+
+/// Splits `text` before every unescaped separator, the separator starts its part.
+fn split_unescaped<'a>(text: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut starts: Vec<usize> = unescaped(text)
+        .filter(|(_,c)| separators.contains(c))
+        .map(|(i,_)| i)
+        .collect();
+    starts.push(text.len());
+    let mut parts = vec![&text[..starts[0]]];
+    parts.extend(starts.windows(2).map(|w| &text[w[0]..w[1]]));
+    parts
+}
+
+/// An answer between the braces: `=correct`, `~wrong` or `~%50%weighted`, with any `#feedback` removed.
+struct Answer {
+    correct: bool,
+    weight: Option<f64>,
+    text: String,
+}
+
+impl Answer {
+    fn parse(part: &str) -> Result<Self, String> {
+        let correct = part.starts_with('=');
+        let part = &part[1..];
+        let part = &part[..find_unescaped(part, '#').unwrap_or(part.len())];
+        let (weight, text) = match part.trim_start().strip_prefix('%') {
+            Some(rest) => {
+                let (weight, text) = rest.split_once('%')
+                    .ok_or_else(|| format!("weight is not closed with `%`: `{}`", part.trim()))?;
+                let weight = weight.parse()
+                    .map_err(|_| format!("weight is not a number: `{}`", weight))?;
+                (Some(weight), text)
+            },
+            None => (None, part),
+        };
+        Ok(Answer { correct, weight, text: unescape(text) })
+    }
+
+    fn is_correct(&self) -> bool {
+        self.weight.map(|w| w > 0.0).unwrap_or(self.correct)
+    }
+}
+
+fn parse_number(number: &str) -> Result<f64, String> {
+    number.trim().parse().map_err(|_| format!("not a number: `{}`", number.trim()))
+}
+
+/// A numerical answer: `#value`, `#value:tolerance` or `#min..max`. Only the first answer is used.
+fn parse_numeric(body: &str) -> Result<QuestionType, String> {
+    let answer = split_unescaped(body, &['='])
+        .into_iter()
+        .map(|part| part.trim_start_matches('='))
+        .find(|part| !part.trim().is_empty())
+        .ok_or("numerical question has no answer")?;
+    let answer = &answer[..find_unescaped(answer, '#').unwrap_or(answer.len())];
+    let answer = match answer.trim().strip_prefix('%') {
+        Some(rest) => rest.split_once('%').map(|(_,a)| a).unwrap_or(rest),
+        None => answer,
+    };
+    let (value, tolerance) = if let Some((min, max)) = answer.split_once("..") {
+        let (min, max) = (parse_number(min)?, parse_number(max)?);
+        ((min + max) / 2.0, Tolerance::Absolute((max - min).abs() / 2.0))
+    } else if let Some(i) = find_unescaped(answer, ':') {
+        (parse_number(&answer[..i])?, Tolerance::Absolute(parse_number(&answer[i+1..])?))
+    } else {
+        (parse_number(answer)?, Tolerance::Exact)
+    };
+    Ok(QuestionType::Numeric { value, unit: None, tolerance })
+}
+
+/// The question type and accepted answers of the answers between the braces.
+fn parse_answers(body: &str) -> Result<(QuestionType, Vec<String>), String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Ok((QuestionType::Open, Vec::new()));
+    }
+    if let Some(numeric) = body.strip_prefix('#') {
+        return Ok((parse_numeric(numeric)?, Vec::new()));
+    }
+    let without_feedback = &body[..find_unescaped(body, '#').unwrap_or(body.len())];
+    let true_false = |answer| QuestionType::MultiChoice { options: vec!["True".to_string(), "False".to_string()], answer };
+    match without_feedback.trim().to_uppercase().as_str() {
+        "T" | "TRUE" => return Ok((true_false(0), Vec::new())),
+        "F" | "FALSE" => return Ok((true_false(1), Vec::new())),
+        _ => {},
+    }
+
+    let parts = split_unescaped(body, &['=', '~']);
+    if !parts[0].trim().is_empty() {
+        return Err(format!("answers start with `=` or `~`: `{}`", parts[0].trim()));
+    }
+    let answers = parts[1..].iter().map(|part| Answer::parse(part)).collect::<Result<Vec<_>,_>>()?;
+    let correct: Vec<usize> = answers.iter().enumerate()
+        .filter_map(|(i,a)| a.is_correct().then_some(i))
+        .collect();
+    let texts: Vec<String> = answers.iter().map(|a| a.text.clone()).collect();
+
+    if answers.iter().all(|a| a.correct) && texts.iter().all(|t| t.contains("->")) {
+        let (mut left, mut right, mut answer) = (Vec::new(), Vec::<String>::new(), Vec::new());
+        for text in &texts {
+            let (l, r) = text.split_once("->").unwrap_or_default();
+            let r = r.trim().to_string();
+            left.push(l.trim().to_string());
+            answer.push(right.iter().position(|o| *o == r).unwrap_or_else(|| { right.push(r); right.len() - 1 }));
+        }
+        Ok((QuestionType::Matching { left, right, answer }, Vec::new()))
+    } else if answers.iter().all(|a| a.correct) {
+        Ok((QuestionType::Open, texts))
+    } else if correct.is_empty() {
+        Err("question has no correct answer".to_string())
+    } else if answers.iter().any(|a| a.weight.is_some()) || correct.len() > 1 {
+        // Moodle subtracts the negative weights, as a fraction of the credit for one correct option
+        let penalties: Vec<f64> = answers.iter().filter_map(|a| a.weight).filter(|w| *w < 0.0).collect();
+        let penalty = match penalties.len() {
+            0 => 0.0,
+            n => penalties.iter().map(|w| w.abs()).sum::<f64>() / n as f64 / 100.0 * correct.len() as f64,
+        };
+        Ok((QuestionType::MultiOption { options: texts, answers: correct, scoring: OptionScoring::PerOption { penalty } }, Vec::new()))
+    } else {
+        Ok((QuestionType::MultiChoice { options: texts, answer: correct[0] }, Vec::new()))
+    }
+}
+
+/// A question: an optional `::name::`, the question text and the answers between braces.
+/// Text after the braces makes a missing word question, with the braces shown as a blank.
+fn parse_question(text: &str, category: &Option<String>) -> Result<Question, String> {
+    let (name, text) = match text.strip_prefix("::") {
+        Some(rest) => {
+            let end = unescaped(rest)
+                .zip(unescaped(rest).skip(1))
+                .find(|((_,a),(_,b))| *a == ':' && *b == ':')
+                .map(|((i,_),_)| i)
+                .ok_or("question name is not closed with `::`")?;
+            (unescape(&rest[..end]), &rest[end+2..])
+        },
+        None => (String::new(), text),
+    };
+    let text = text.trim_start();
+    let text = match text.strip_prefix('[') {
+        Some(rest) if ["html]", "moodle]", "markdown]", "plain]"].iter().any(|f| rest.starts_with(f)) =>
+            &rest[rest.find(']').unwrap_or(0)+1..],
+        _ => text,
+    };
+    let open = find_unescaped(text, '{').ok_or("question has no answers between `{` and `}`")?;
+    let close = find_unescaped(&text[open..], '}').map(|i| open + i).ok_or("answers are not closed with `}`")?;
+    if find_unescaped(&text[close+1..], '{').is_some() {
+        return Err("questions are separated by a blank line".to_string());
+    }
+    let (before, after) = (unescape(&text[..open]), unescape(&text[close+1..]));
+    let title = if after.is_empty() { before } else { format!("{} _____ {}", before, after) };
+
+    let (type_spec, accepted) = parse_answers(&text[open+1..close])?;
+    let mut question = Question::new(title, type_spec)
+        .with_accept(AcceptedAnswers::new(accepted))
+        .with_category(category.clone(), None);
+    if !name.is_empty() {
+        question = question.with_id(name);
+    }
+    Ok(question)
+}
+
+/// Parses a GIFT file into a quiz with the given title.
+/// Returns the quiz and the questions that were left out because they could not be read.
+pub fn parse(data: &str, title: String) -> Result<(Config, Vec<String>), String> {
+    let mut category = None;
+    let mut questions = Vec::new();
+    let mut skipped = Vec::new();
+    let mut names = HashMap::new();
+    let mut block: Vec<&str> = Vec::new();
+    let mut block_start = 0;
+
+    let mut finish = |block: &mut Vec<&str>, start: usize, category: &Option<String>| {
+        if !block.is_empty() {
+            match parse_question(block.join("\n").trim(), category) {
+                Ok(question) if question.id().is_empty() => questions.push(question),
+                Ok(question) => match names.get(question.id()) {
+                    Some(first) => skipped.push(format!("question at line {}: skipped, name `{}` is already used at line {}", start, question.id(), first)),
+                    None => {
+                        names.insert(question.id().clone(), start);
+                        questions.push(question);
+                    },
+                },
+                Err(e) => skipped.push(format!("question at line {}: skipped, {}", start, e)),
+            }
+            block.clear();
+        }
+    };
+    for (i, line) in data.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") {
+            continue;
+        }
+        if trimmed.is_empty() {
+            finish(&mut block, block_start, &category);
+        } else if let Some(name) = trimmed.strip_prefix("$CATEGORY:") {
+            finish(&mut block, block_start, &category);
+            let name = name.trim().trim_start_matches("$course$/").trim_start_matches("top/");
+            category = Some(name.to_string()).filter(|c| !c.is_empty());
+        } else {
+            if block.is_empty() {
+                block_start = i + 1;
+            }
+            block.push(line);
+        }
+    }
+    finish(&mut block, block_start, &category);
+
+    Ok((Config::new(title, questions)?, skipped))
+}
+
+/// A weight in percent with at most five decimals, as Moodle writes them.
+fn percent(weight: f64) -> String {
+    let weight = format!("{:.5}", weight);
+    weight.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The answers between the braces, `None` for question types GIFT has no equivalent for.
+/// Settings that GIFT can not express are added to `losses`.
+fn write_answers(question: &Question, losses: &mut Vec<String>) -> Option<String> {
+    let answers = match question.type_spec() {
+        QuestionType::MultiChoice{options,answer} => options.iter().enumerate()
+            .map(|(i,o)| format!("{}{}", if i == *answer { "=" } else { "~" }, escape(o)))
+            .collect(),
+        QuestionType::MultiOption{options,answers,scoring} => {
+            let credit = 100.0 / answers.len().max(1) as f64;
+            let penalty = match scoring {
+                OptionScoring::AllOrNothing => {
+                    losses.push("all or nothing scoring is written as weighted answers".to_string());
+                    100.0
+                },
+                OptionScoring::PerOption{penalty} => (penalty * credit).min(100.0),
+            };
+            options.iter().enumerate()
+                .map(|(i,o)| format!("~%{}%{}", percent(if answers.contains(&i) { credit } else { -penalty }), escape(o)))
+                .collect()
+        },
+        QuestionType::Matching{left,right,answer} => left.iter().zip(answer)
+            .map(|(l,r)| format!("={} -> {}", escape(l), escape(right.get(*r).map(|s| s.as_str()).unwrap_or_default())))
+            .collect(),
+        QuestionType::Numeric{value,unit,tolerance} => {
+            if let Some(unit) = unit {
+                losses.push(format!("unit `{}` is left out", unit));
+            }
+            vec![match tolerance {
+                Tolerance::Absolute(t) => format!("#{}:{}", value, t),
+                Tolerance::Relative(t) => format!("#{}:{}", value, (value * t).abs()),
+                Tolerance::Exact => format!("#{}", value),
+                Tolerance::Closest => {
+                    losses.push("closest wins scoring is written as an exact answer".to_string());
+                    format!("#{}", value)
+                },
+            }]
+        },
+        QuestionType::Open => {
+            if !question.accept().patterns().is_empty() {
+                losses.push(format!("accepted patterns are left out: {}", question.accept().patterns().join(", ")));
+            }
+            question.accept().answers().iter().map(|a| format!("={}", escape(a))).collect()
+        },
+        QuestionType::Ordering{..} | QuestionType::Poll{..} => return None,
+    };
+    if question.min_score() != 0.0 || question.max_score() != 1.0 {
+        losses.push(format!("grade range {}..{} is left out", question.min_score(), question.max_score()));
+    }
+    if let Some(image) = question.image() {
+        losses.push(format!("image `{}` is left out", image.display()));
+    }
+    Some(answers.join(" "))
+}
+
+/// Writes the questions of a quiz in GIFT, returns the file and the questions that were left out or changed.
+pub fn write(config: &Config) -> (String, Vec<String>) {
+    let mut gift = format!("// {}\n\n", config.title());
+    let mut skipped = Vec::new();
+    let mut category = None;
+    for (i, question) in config.questions().iter().enumerate() {
+        let mut losses = Vec::new();
+        let answers = write_answers(question, &mut losses);
+        skipped.extend(losses.into_iter().map(|loss| format!("question {} ({}): {}", i, question.id(), loss)));
+        let Some(answers) = answers else {
+            let kind = question.type_spec().to_string();
+            skipped.push(format!("question {} ({}): skipped, {} questions can not be written in GIFT", i, question.id(), kind.lines().next().unwrap_or_default()));
+            continue;
+        };
+        if question.category() != category {
+            category = question.category();
+            gift += &format!("$CATEGORY: {}\n\n", category.map(|c| c.as_str()).unwrap_or_default());
+        }
+        gift += &format!("::{}:: {} {{{}}}\n\n", escape(question.id()), escape(question.title()), answers);
+    }
+    (gift, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::GradeRange;
+
+    fn parse_one(gift: &str) -> Question {
+        let (config, skipped) = parse(gift, "test".to_string()).unwrap();
+        assert!(skipped.is_empty(), "{:?}", skipped);
+        config.questions()[0].clone()
+    }
+
+    #[test]
+    fn escaping_round_trips_special_characters() {
+        let text = r"a = b ~ c # d {e} f: g \ h";
+        let escaped = escape(text);
+        assert_eq!(escaped, r"a \= b \~ c \# d \{e\} f\: g \\ h");
+        assert_eq!(unescape(&escaped), text);
+        assert_eq!(parse_one(&format!("{} {{=x ~y}}", escaped)).title(), text);
+    }
+
+    #[test]
+    fn weighted_answers_become_per_option_scoring() {
+        let question = parse_one("Planets? {~%50%Mars ~%-100%Moon ~%50%Venus}");
+        match question.type_spec() {
+            QuestionType::MultiOption { options, answers, scoring: OptionScoring::PerOption { penalty } } => {
+                assert_eq!(options, &["Mars", "Moon", "Venus"]);
+                assert_eq!(answers, &[0, 2]);
+                // -100% takes the full grade, which is twice the credit of one of the two correct options
+                assert_eq!(*penalty, 2.0);
+            },
+            other => panic!("unexpected type {:?}", other),
+        }
+    }
+
+    #[test]
+    fn numeric_answers_with_tolerance_and_range() {
+        let question = parse_one("Height? {#330:15}");
+        assert!(matches!(question.type_spec(), QuestionType::Numeric { value, tolerance: Tolerance::Absolute(t), .. } if *value == 330.0 && *t == 15.0));
+        let question = parse_one("Range? {#1..5}");
+        assert!(matches!(question.type_spec(), QuestionType::Numeric { value, tolerance: Tolerance::Absolute(t), .. } if *value == 3.0 && *t == 2.0));
+        let question = parse_one("Exact? {#42}");
+        assert!(matches!(question.type_spec(), QuestionType::Numeric { value, tolerance: Tolerance::Exact, .. } if *value == 42.0));
+    }
+
+    #[test]
+    fn matching_pairs_share_right_items() {
+        let question = parse_one("Match! {=Paris -> France =Lyon -> France =Rome -> Italy}");
+        match question.type_spec() {
+            QuestionType::Matching { left, right, answer } => {
+                assert_eq!(left, &["Paris", "Lyon", "Rome"]);
+                assert_eq!(right, &["France", "Italy"]);
+                assert_eq!(answer, &[0, 0, 1]);
+            },
+            other => panic!("unexpected type {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unreadable_items_are_skipped_with_their_line() {
+        let gift = "A description.\n\n::a:: One? {=x ~y}\n\n::a:: Two? {=x ~y}\n\n::b:: Three? {=x ~y}\n";
+        let (config, skipped) = parse(gift, "test".to_string()).unwrap();
+        assert_eq!(config.questions().iter().map(|q| q.id().as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped[0].starts_with("question at line 1: skipped"));
+        assert!(skipped[1].starts_with("question at line 5: skipped, name `a`"));
+    }
+
+    #[test]
+    fn settings_gift_can_not_hold_are_reported() {
+        let options = vec!["a".to_string(), "b".to_string()];
+        let question = Question::new("Which?".into(), QuestionType::MultiOption { options, answers: vec![0], scoring: OptionScoring::AllOrNothing })
+            .with_grade_range(GradeRange::new(0.0, 3.0))
+            .with_image("map.png".into());
+        let mut losses = Vec::new();
+        assert!(write_answers(&question, &mut losses).is_some());
+        assert_eq!(losses, [
+            "all or nothing scoring is written as weighted answers",
+            "grade range 0..3 is left out",
+            "image `map.png` is left out",
+        ]);
+    }
+
+    #[test]
+    fn parse_write_parse_round_trip() {
+        let gift = "$CATEGORY: geography\n\n\
+            ::capital:: Which city is the capital of France? {=Paris ~Lyon ~Marseille}\n\n\
+            ::planets:: Which are planets? {~%50%Mars ~%-100%Moon ~%50%Venus}\n\n\
+            ::rings:: Name a planet with rings. {=Saturn =Jupiter}\n\n\
+            ::eiffel:: How many meters tall is the Eiffel tower? {#330:15}\n\n\
+            ::match:: Match the capitals. {=Paris -> France =Rome -> Italy}\n";
+        let (first, skipped) = parse(gift, "test".to_string()).unwrap();
+        assert!(skipped.is_empty(), "{:?}", skipped);
+        let (written, lost) = write(&first);
+        assert!(lost.is_empty(), "{:?}", lost);
+        let (second, skipped) = parse(&written, "test".to_string()).unwrap();
+        assert!(skipped.is_empty(), "{:?}", skipped);
+
+        assert_eq!(first.questions().len(), 5);
+        assert_eq!(first.questions().len(), second.questions().len());
+        for (a, b) in first.questions().iter().zip(second.questions()) {
+            assert_eq!(a.id(), b.id());
+            assert_eq!(a.title(), b.title());
+            assert_eq!(a.category(), b.category());
+            assert_eq!(a.accept().answers(), b.accept().answers());
+            assert_eq!(format!("{:?}", a.type_spec()), format!("{:?}", b.type_spec()));
+        }
+    }
+}
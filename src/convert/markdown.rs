@@ -0,0 +1,196 @@
+//! Quizzes written in Markdown:
+//!
+//! ```markdown
+//! # Quiz title
+//!
+//! ## Which city is the capital of France?
+//! ![Eiffel tower](eiffel.png)
+//! - [ ] Lyon
+//! - [x] Paris
+//!
+//! ## Name a planet with rings.
+//! > answer: Saturn
+//! > answer: Jupiter
+//! > points: 2
+//! ```
+//!
+//! Every heading below the title is a question. One ticked option makes a multi-choice question,
+//! more make a multi-option question and none a poll. Questions without options are open questions,
+//! graded against the `> answer:` lines.
+
+use std::path::PathBuf;
+
+use crate::state::{AcceptedAnswers, Config, GradeRange, Question, QuestionType};
+
+/// A question while its lines are being read.
+struct Draft {
+    line: usize,
+    title: String,
+    image: Option<PathBuf>,
+    options: Vec<(String, bool)>,
+    answers: Vec<String>,
+    points: f64,
+}
+
+impl Draft {
+    fn new(line: usize, title: String) -> Self {
+        Draft { line, title, image: None, options: Vec::new(), answers: Vec::new(), points: 1.0 }
+    }
+
+    fn into_question(self) -> Result<Question, String> {
+        if !self.options.is_empty() && !self.answers.is_empty() {
+            return Err(format!("line {}: question has both options and answers", self.line));
+        }
+        let correct: Vec<usize> = self.options.iter().enumerate()
+            .filter_map(|(i,(_,correct))| correct.then_some(i))
+            .collect();
+        let options: Vec<String> = self.options.into_iter().map(|(option,_)| option).collect();
+        let (type_spec, max) = match correct.as_slice() {
+            _ if options.is_empty() => (QuestionType::Open, self.points),
+            [] => (QuestionType::Poll { options }, 0.0),
+            [answer] => (QuestionType::MultiChoice { options, answer: *answer }, self.points),
+            _ => (QuestionType::MultiOption { options, answers: correct, scoring: Default::default() }, self.points),
+        };
+        let mut question = Question::new(self.title, type_spec)
+            .with_grade_range(GradeRange::new(0.0, max))
+            .with_accept(AcceptedAnswers::new(self.answers));
+        if let Some(image) = self.image {
+            question = question.with_image(image);
+        }
+        Ok(question)
+    }
+}
+
+/// The option of a `- [x] option` or `- [ ] option` line and whether it is ticked.
+fn parse_option(line: &str) -> Option<(String, bool)> {
+    let rest = line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))?;
+    let (mark, option) = rest.split_at_checked(3)?;
+    let correct = match mark {
+        "[x]" | "[X]" => true,
+        "[ ]" => false,
+        _ => return None,
+    };
+    Some((option.trim().to_string(), correct))
+}
+
+/// The path of a `![alt](path "title")` line.
+fn parse_image(line: &str) -> Option<PathBuf> {
+    let rest = line.strip_prefix("![")?;
+    let (_, link) = rest.split_once("](")?;
+    let link = link.strip_suffix(')')?;
+    link.split_whitespace().next().map(PathBuf::from)
+}
+
+/// Parses a quiz written in Markdown, reporting every line that could not be read.
+pub fn parse(data: &str) -> Result<Config, String> {
+    let mut title = None;
+    let mut draft: Option<Draft> = None;
+    let mut questions = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in data.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("# ") {
+            if title.is_some() || draft.is_some() {
+                errors.push(format!("line {}: the quiz title must be the first heading and there can be only one", number));
+            } else {
+                title = Some(heading.trim().to_string());
+            }
+        } else if line.starts_with("##") {
+            let heading = line.trim_start_matches('#').trim();
+            if heading.is_empty() {
+                errors.push(format!("line {}: question has no title", number));
+            }
+            if let Some(draft) = draft.replace(Draft::new(number, heading.to_string())) {
+                questions.push(draft.into_question());
+            }
+        } else if let Some(current) = draft.as_mut() {
+            if let Some(option) = parse_option(line) {
+                current.options.push(option);
+            } else if let Some(image) = parse_image(line) {
+                current.image = Some(image);
+            } else if let Some(answer) = line.strip_prefix("> answer:") {
+                current.answers.push(answer.trim().to_string());
+            } else if let Some(points) = line.strip_prefix("> points:") {
+                match points.trim().parse() {
+                    Ok(points) => current.points = points,
+                    Err(_) => errors.push(format!("line {}: points must be a number: `{}`", number, points.trim())),
+                }
+            } else {
+                errors.push(format!("line {}: expected an option, image or answer: `{}`", number, line));
+            }
+        } else {
+            errors.push(format!("line {}: expected the quiz title or a question heading: `{}`", number, line));
+        }
+    }
+    if let Some(draft) = draft {
+        questions.push(draft.into_question());
+    }
+
+    let questions = questions.into_iter()
+        .filter_map(|q| q.map_err(|e| errors.push(e)).ok())
+        .collect();
+    let Some(title) = title else {
+        errors.push("the quiz has no title, start with a `# Title` heading".to_string());
+        return Err(errors.join("\n"));
+    };
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Config::new(title, questions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options_images_and_answers() {
+        let quiz = "# Pub quiz\n\n\
+            ## Which city is the capital of France?\n\
+            ![Eiffel tower](eiffel.png \"tower\")\n\
+            - [ ] Lyon\n\
+            * [X] Paris\n\n\
+            ## Which are planets?\n\
+            - [x] Mars\n- [ ] Moon\n- [x] Venus\n\n\
+            ### Name a planet with rings.\n\
+            > answer: Saturn\n> answer: Jupiter\n> points: 2\n\n\
+            ## Favourite round?\n\
+            - [ ] First\n- [ ] Second\n";
+        let config = parse(quiz).unwrap();
+        assert_eq!(config.title(), "Pub quiz");
+        let questions = config.questions();
+        assert_eq!(questions.len(), 4);
+
+        assert_eq!(questions[0].image(), Some(&PathBuf::from("eiffel.png")));
+        assert!(matches!(questions[0].type_spec(), QuestionType::MultiChoice { options, answer: 1 } if options == &["Lyon", "Paris"]));
+        assert!(matches!(questions[1].type_spec(), QuestionType::MultiOption { answers, .. } if answers == &[0, 2]));
+        assert!(matches!(questions[2].type_spec(), QuestionType::Open));
+        assert_eq!(questions[2].accept().answers(), &["Saturn", "Jupiter"]);
+        assert_eq!(questions[2].max_score(), 2.0);
+        assert!(matches!(questions[3].type_spec(), QuestionType::Poll { .. }));
+        assert_eq!(questions[3].max_score(), 0.0);
+    }
+
+    #[test]
+    fn reports_every_problem_with_its_line() {
+        let quiz = "## No title first\n- [x] a\n\n# Title\n## Mixed\n- [x] a\n> answer: b\n## Points\n> points: many\nstray text\n";
+        let errors = parse(quiz).unwrap_err();
+        let lines: Vec<_> = errors.lines().collect();
+        assert_eq!(lines.len(), 5, "{}", errors);
+        assert!(lines[0].starts_with("line 4: the quiz title must be the first heading"));
+        assert!(lines[1].starts_with("line 9: points must be a number"));
+        assert!(lines[2].starts_with("line 10: expected an option"));
+        assert!(lines[3].starts_with("line 5: question has both options and answers"));
+        assert!(lines[4].contains("no title"));
+    }
+
+    #[test]
+    fn a_title_is_required() {
+        assert!(parse("## Question\n> answer: a\n").unwrap_err().contains("no title"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Questions exported from a spreadsheet as CSV, one question per row under a header row with the columns:
+//!
+//! | title | type | options | answer | min | max | image |
+//! |-------|------|---------|--------|-----|-----|-------|
+//!
+//! `type` is one of multichoice, multioption, ordering, poll, numeric or open. Options and answers are
+//! separated by `|`, answers are written out as the text of the correct options. Ordering questions list
+//! the options in the correct order as answer, open questions list the accepted answers. The grade range
+//! is 0 to 1 when `min` and `max` are empty, `image` is optional. Empty columns at the end of a row may be left out.
+
+use serde::Deserialize;
+
+use crate::state::{AcceptedAnswers, Config, GradeRange, Question, QuestionType};
+
+#[derive(Deserialize)]
+struct Row {
+    title: String,
+    #[serde(rename = "type")]
+    type_: String,
+    #[serde(default)]
+    options: String,
+    #[serde(default)]
+    answer: String,
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+    #[serde(default)]
+    image: Option<String>,
+}
+
+fn split(cell: &str) -> Vec<String> {
+    cell.split('|')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Indices of the answers in the options.
+fn find_answers(options: &[String], answers: &[String]) -> Result<Vec<usize>, String> {
+    answers.iter()
+        .map(|answer| options.iter().position(|o| o == answer)
+            .ok_or_else(|| format!("answer `{}` is not one of the options", answer)))
+        .collect()
+}
+
+impl Row {
+    fn into_question(self) -> Result<Question, String> {
+        if self.title.is_empty() {
+            return Err("title is empty".to_string());
+        }
+        let options = split(&self.options);
+        let answers = split(&self.answer);
+        let mut accept = AcceptedAnswers::default();
+        let type_spec = match self.type_.to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "multichoice" => match find_answers(&options, &answers)?.as_slice() {
+                [answer] => QuestionType::MultiChoice { options, answer: *answer },
+                _ => return Err("a multichoice question needs exactly one answer".to_string()),
+            },
+            "multioption" => QuestionType::MultiOption { answers: find_answers(&options, &answers)?, options, scoring: Default::default() },
+            "ordering" => {
+                let answer = find_answers(&options, &answers)?;
+                if answer.len() != options.len() {
+                    return Err("the answer of an ordering question lists every option in the correct order".to_string());
+                }
+                QuestionType::Ordering { options, answer, scoring: Default::default() }
+            },
+            "poll" => QuestionType::Poll { options },
+            "numeric" => {
+                let value = self.answer.parse()
+                    .map_err(|_| format!("answer `{}` is not a number", self.answer))?;
+                QuestionType::Numeric { value, unit: None, tolerance: Default::default() }
+            },
+            "open" => {
+                accept = AcceptedAnswers::new(answers);
+                QuestionType::Open
+            },
+            other => return Err(format!("unknown question type `{}`", other)),
+        };
+        let max = if matches!(type_spec, QuestionType::Poll{..}) { 0.0 } else { 1.0 };
+        let mut question = Question::new(self.title, type_spec)
+            .with_grade_range(GradeRange::new(self.min.unwrap_or(0.0), self.max.unwrap_or(max)))
+            .with_accept(accept);
+        if let Some(image) = self.image.filter(|i| !i.is_empty()) {
+            question = question.with_image(image.into());
+        }
+        Ok(question)
+    }
+}
+
+/// Parses the rows of a CSV file into a quiz with the given title, reporting every row that could not be read.
+pub fn parse(data: &str, title: String) -> Result<Config, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(data.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Could not read the header row: {}", e))?.clone();
+
+    let mut questions = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let mut record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(e.to_string());
+                continue;
+            }
+        };
+        while record.len() < headers.len() {
+            record.push_field("");
+        }
+        let row = record.position().map(|p| p.line()).unwrap_or_default();
+        match record.deserialize::<Row>(Some(&headers)).map_err(|e| e.to_string()).and_then(Row::into_question) {
+            Ok(question) => questions.push(question),
+            Err(e) => errors.push(format!("row {}: {}", row, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Config::new(title, questions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_rows(rows: &str) -> Result<Config, String> {
+        parse(&format!("title,type,options,answer,min,max,image\n{}", rows), "test".to_string())
+    }
+
+    #[test]
+    fn parses_every_question_type() {
+        let config = parse_rows("\
+            Capital?,MultiChoice,Lyon|Paris,Paris,,,eiffel.png\n\
+            Planets?,multi-option,Mars | Moon | Venus,Mars|Venus,0,2\n\
+            Order!,ordering,b|a|c,a|b|c\n\
+            Favourite?,poll,x|y\n\
+            Height?,numeric,,330.5\n\
+            Rings?,open,,Saturn|Jupiter,-1,1\n").unwrap();
+        let questions = config.questions();
+        assert_eq!(questions.len(), 6);
+        assert!(matches!(questions[0].type_spec(), QuestionType::MultiChoice { answer: 1, .. }));
+        assert_eq!(questions[0].image(), Some(&"eiffel.png".into()));
+        assert!(matches!(questions[1].type_spec(), QuestionType::MultiOption { options, answers, .. } if options == &["Mars", "Moon", "Venus"] && answers == &[0, 2]));
+        assert_eq!(questions[1].max_score(), 2.0);
+        assert!(matches!(questions[2].type_spec(), QuestionType::Ordering { answer, .. } if answer == &[1, 0, 2]));
+        assert_eq!(questions[3].max_score(), 0.0);
+        assert!(matches!(questions[4].type_spec(), QuestionType::Numeric { value, .. } if *value == 330.5));
+        assert_eq!(questions[5].accept().answers(), &["Saturn", "Jupiter"]);
+        assert_eq!(questions[5].min_score(), -1.0);
+    }
+
+    #[test]
+    fn reports_every_row_that_could_not_be_read() {
+        let errors = parse_rows("\
+            Good?,open\n\
+            Capital?,multichoice,Lyon|Paris,Rome\n\
+            Order!,ordering,a|b|c,a|b\n\
+            ,open\n\
+            What?,essay\n").unwrap_err();
+        let lines: Vec<_> = errors.lines().collect();
+        assert_eq!(lines, [
+            "row 3: answer `Rome` is not one of the options",
+            "row 4: the answer of an ordering question lists every option in the correct order",
+            "row 5: title is empty",
+            "row 6: unknown question type `essay`",
+        ]);
+    }
+}
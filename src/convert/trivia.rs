@@ -0,0 +1,113 @@
+//! Dumps of the Open Trivia DB api, either the full response or only its `results`:
+//!
+//! ```json
+//! { "response_code": 0, "results": [{
+//!     "type": "multiple", "difficulty": "easy", "category": "Geography",
+//!     "question": "What is the capital of France?",
+//!     "correct_answer": "Paris", "incorrect_answers": ["Lyon", "Marseille", "Nice"]
+//! }] }
+//! ```
+//!
+//! Questions become multi-choice questions with shuffled options, true/false questions keep their order.
+//! HTML entities are decoded and the category and difficulty are kept to draw questions from pools.
+
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::state::{Config, Question, QuestionType};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Dump {
+    Response { results: Vec<Trivia> },
+    Results(Vec<Trivia>),
+}
+
+#[derive(Deserialize)]
+struct Trivia {
+    #[serde(rename = "type", default)]
+    type_: String,
+    #[serde(default)]
+    difficulty: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    question: String,
+    correct_answer: String,
+    incorrect_answers: Vec<String>,
+}
+
+fn decode(text: &str) -> String {
+    html_escape::decode_html_entities(text).trim().to_string()
+}
+
+impl Trivia {
+    fn into_question(self) -> Question {
+        let correct = decode(&self.correct_answer);
+        let mut options: Vec<String> = self.incorrect_answers.iter().map(|a| decode(a)).collect();
+        if self.type_ == "boolean" {
+            options = vec!["True".to_string(), "False".to_string()];
+        } else {
+            options.push(correct.clone());
+            options.shuffle(&mut rand::rng());
+        }
+        let answer = options.iter().position(|o| o.eq_ignore_ascii_case(&correct)).unwrap_or_default();
+        Question::new(decode(&self.question), QuestionType::MultiChoice { options, answer })
+            .with_category(self.category.map(|c| decode(&c)), self.difficulty)
+    }
+}
+
+/// Whether `data` looks like an Open Trivia DB dump rather than a quiz config.
+pub fn is_dump(data: &str) -> bool {
+    serde_json::from_str::<Dump>(data).is_ok()
+}
+
+/// Parses an Open Trivia DB dump into a quiz with the given title.
+pub fn parse(data: &str, title: String) -> Result<Config, String> {
+    let dump: Dump = serde_json::from_str(data)
+        .map_err(|e| format!("Not an Open Trivia DB dump: {}", e))?;
+    let (Dump::Response{results} | Dump::Results(results)) = dump;
+    Config::new(title, results.into_iter().map(Trivia::into_question).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = r#"{ "response_code": 0, "results": [
+        { "type": "multiple", "difficulty": "easy", "category": "Entertainment: Film &amp; TV",
+          "question": "Who directed &quot;Am&eacute;lie&quot;?",
+          "correct_answer": "Jean-Pierre Jeunet", "incorrect_answers": ["Luc Besson", "Fran&ccedil;ois Ozon", "Agn&egrave;s Varda"] },
+        { "type": "boolean", "difficulty": "hard", "category": "Science",
+          "question": "The Moon is a planet.", "correct_answer": "False", "incorrect_answers": ["True"] }
+    ] }"#;
+
+    #[test]
+    fn decodes_entities_and_keeps_categories() {
+        assert!(is_dump(DUMP));
+        let config = parse(DUMP, "trivia".to_string()).unwrap();
+        let question = &config.questions()[0];
+        assert_eq!(question.title(), "Who directed \"Amélie\"?");
+        assert_eq!(question.category().map(|c| c.as_str()), Some("Entertainment: Film & TV"));
+        assert_eq!(question.difficulty().map(|d| d.as_str()), Some("easy"));
+        match question.type_spec() {
+            QuestionType::MultiChoice { options, answer } => {
+                assert_eq!(options.len(), 4);
+                assert_eq!(options[*answer], "Jean-Pierre Jeunet");
+                assert!(options.contains(&"François Ozon".to_string()));
+            },
+            other => panic!("unexpected type {:?}", other),
+        }
+    }
+
+    #[test]
+    fn boolean_questions_keep_true_and_false_in_order() {
+        let config = parse(DUMP, "trivia".to_string()).unwrap();
+        assert!(matches!(config.questions()[1].type_spec(), QuestionType::MultiChoice { options, answer: 1 } if options == &["True", "False"]));
+    }
+
+    #[test]
+    fn results_alone_are_a_dump_but_a_quiz_config_is_not() {
+        assert!(is_dump(r#"[{ "question": "Q?", "correct_answer": "a", "incorrect_answers": ["b"] }]"#));
+        assert!(!is_dump(r#"{ "title": "Quiz", "questions": [] }"#));
+    }
+}
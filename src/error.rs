@@ -7,6 +7,8 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("Json error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Csv error: {0}")]
+    Csv(#[from] csv::Error),
     #[error("Http error: {0}")]
     Http(#[from] http::Error),
     #[error("Hyper error: {0}")]
@@ -1,21 +1,27 @@
 
 #[macro_use]
 mod cli;
+mod convert;
 mod error;
 mod server;
 mod state;
 
 use std::{net::SocketAddr, path::PathBuf};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 
 /// A simple quiz server app
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, args_conflicts_with_subcommands = true)]
 pub struct AppArgs {
-    /// The root of the server, a quiz.config (json) file should be located here.
-    #[arg(name = "ROOT")]
-    root: String,
+    #[command(subcommand)]
+    command: Option<AppCommand>,
+    /// The root of the server, a quiz.config (json), quiz.yaml or quiz.toml file should be located here.
+    #[arg(name = "ROOT", required = true)]
+    root: Option<String>,
+    /// The format of the quiz config: json, yaml or toml. Detected by the file extension by default.
+    #[arg(long = "format")]
+    format: Option<state::ConfigFormat>,
     /// The ip address to bind to: Ipv4, Ipv6 or localhost.
     #[arg(long = "ip")]
     ip: Option<String>,
@@ -24,10 +30,52 @@ pub struct AppArgs {
     port: Option<String>,
 }
 
-fn init() -> Result<(PathBuf, state::Config,SocketAddr),String> {
-	let args = AppArgs::parse();
+#[derive(Subcommand, Debug)]
+enum AppCommand {
+    /// Check the quiz.config in ROOT and report all problems without starting the server.
+    Validate {
+        #[arg(name = "ROOT")]
+        root: String,
+        /// The format of the quiz config: json, yaml or toml. Detected by the file extension by default.
+        #[arg(long = "format")]
+        format: Option<state::ConfigFormat>,
+    },
+    /// Convert questions written in another format into a quiz.config, or a quiz into GIFT.
+    Convert {
+        /// The file with questions to convert, or a quiz root.
+        #[arg(name = "INPUT")]
+        input: PathBuf,
+        /// The file to write, a quiz.config next to the input by default.
+        #[arg(short = 'o', long = "output")]
+        output: Option<PathBuf>,
+        /// The format of the input: config, markdown, csv, gift or opentdb. Detected by the file extension by default.
+        #[arg(long = "from")]
+        from: Option<convert::SourceFormat>,
+        /// The format of the output: config or gift. Detected by the file extension by default.
+        #[arg(long = "to")]
+        to: Option<convert::TargetFormat>,
+        /// Overwrite the output if it exists.
+        #[arg(long = "force")]
+        force: bool,
+    },
+}
+
+fn validate(root: &str, format: Option<state::ConfigFormat>) -> Result<(),String> {
+	let problems = state::validate_config(root, format)?;
+	if problems.is_empty() {
+		println!("No problems found in the quiz config.");
+		Ok(())
+	} else {
+		for problem in &problems {
+			println!("{}", problem);
+		}
+		Err(format!("Found {} problem(s) in the quiz config.", problems.len()))
+	}
+}
 
-	let (root, config) = state::get_config(&args.root)?;
+fn init(args: AppArgs) -> Result<(PathBuf, state::Config,SocketAddr),String> {
+	let root = args.root.unwrap_or_default();
+	let (root, config) = state::get_config(&root, args.format)?;
 	Ok((
 		root, config,
 		server::get_socket(args.ip,args.port)?
@@ -36,13 +84,35 @@ fn init() -> Result<(PathBuf, state::Config,SocketAddr),String> {
 
 #[tokio::main]
 async fn main() {
-    match init() {
+    let args = AppArgs::parse();
+    if let Some(command) = &args.command {
+        let result = match command {
+            AppCommand::Validate{root,format} => validate(root, *format),
+            AppCommand::Convert{input,output,from,to,force} => convert::convert(input, output.as_deref(), *from, *to, *force)
+                .map(|(output, skipped)| {
+                    for problem in skipped {
+                        println!("{}", problem);
+                    }
+                    println!("Wrote: {:?}", output);
+                }),
+        };
+        if let Err(e) = result {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    match init(args) {
         Ok((root, config, socket)) => {
             println!("Starting quiz server in: {:?}", root);
             println!("Socket: {:?}", socket);
+            println!("Seed: {}", config.seed());
+            let admin_token = server::admin_token();
+            println!("Report: http://{}/report?token={}", socket, admin_token);
 
 			let state = state::create_quiz_state(root, config);
-			let sse = server::start(&state, socket).await;
+			let sse = server::start(&state, socket, admin_token).await;
 			cli::start(state, sse).await;
         },
         Err(e) => {
@@ -30,11 +30,17 @@ pub fn get_socket(ip: Option<String>, port: Option<String>) -> Result<SocketAddr
 	Ok(SocketAddr::new(ip,port))
 }
 
-pub async fn start(state: &QuizStateService, socket: SocketAddr) -> SseService  {
+/// Random token that gives access to admin pages such as the report.
+pub fn admin_token() -> String {
+    use rand::{Rng, distr::Alphanumeric};
+    rand::rng().sample_iter(&Alphanumeric).take(24).map(char::from).collect()
+}
+
+pub async fn start(state: &QuizStateService, socket: SocketAddr, admin_token: String) -> SseService  {
     let (job_sender, job_receiver) = channel(1000);
     sse::create_sse_state(job_receiver);
     let sse = SseService::new(job_sender);
 
-    listener::start(state, &sse, socket).await;
+    listener::start(state, &sse, socket, admin_token).await;
     sse
 }
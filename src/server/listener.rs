@@ -11,16 +11,16 @@ use crate::state::QuizStateService;
 
 use super::{serve, SseService};
 
-pub async fn start(state: &QuizStateService, sse: &SseService, socket: SocketAddr) {
+pub async fn start(state: &QuizStateService, sse: &SseService, socket: SocketAddr, admin_token: String) {
     let state = state.clone();
     let sse = sse.clone();
     tokio::task::spawn(async move {
         let listener = TcpListener::bind(socket).await.unwrap();
-        handle_requests(state.clone(), sse.clone(), listener).await;
+        handle_requests(state.clone(), sse.clone(), listener, admin_token).await;
     });
 }
 
-async fn handle_requests(state: QuizStateService, sse: SseService, listener: TcpListener) {
+async fn handle_requests(state: QuizStateService, sse: SseService, listener: TcpListener, admin_token: String) {
     loop {
         let (tcp, _remote_address) = listener.accept().await.unwrap();
         //quiz_print!("accepted connection from {:?}", _remote_address);
@@ -28,11 +28,13 @@ async fn handle_requests(state: QuizStateService, sse: SseService, listener: Tcp
 
         let state_clone = state.clone();
         let sse_clone = sse.clone();
+        let token_clone = admin_token.clone();
         tokio::task::spawn(async move {
             let service = hyper::service::service_fn(|req| {
                 let state = state_clone.clone();
                 let sse = sse_clone.clone();
-                main_service(state,sse,req)
+                let admin_token = token_clone.clone();
+                main_service(state,sse,admin_token,req)
             });
 
             let conn = ConnectionBuilder::new()
@@ -51,6 +53,7 @@ async fn handle_requests(state: QuizStateService, sse: SseService, listener: Tcp
 async fn main_service(
     state: QuizStateService, 
     sse: SseService, 
+    admin_token: String,
     req: http::Request<Incoming>
 ) 
 -> QuizResult<http::Response<serve::Body>> 
@@ -62,10 +65,14 @@ async fn main_service(
     match (parts.method, parts.uri.path()) {
         (Method::POST, "/login")          => serve::login_answer(state,sse,body.unwrap()).await,
         (Method::POST, "/relogin")        => serve::relogin_answer(state,body.unwrap()).await,
+        (Method::POST, "/join_team")      => serve::join_team(state,sse,body.unwrap()).await,
         (Method::POST, "/submit_answer")  => serve::submit_answer(state,body.unwrap()).await,
+        (Method::POST, "/buzz")           => serve::buzz(state,sse,body.unwrap()).await,
+        (Method::POST, "/wager")          => serve::place_wager(state,body.unwrap()).await,
         (Method::POST, "/last_event")     => serve::last_event(state,sse,body.unwrap()).await,
         (Method::GET, "/sse")             => serve::sse(sse).await,
         (Method::GET, "/title")           => serve::title(state).await,
+        (Method::GET, "/report")          => serve::report(state,parts.uri.query(),&admin_token).await,
         (Method::GET, file)         => serve::file(state,file.to_string()).await,
         (_,loc) => {
             quiz_print!("Unknown request: {}", loc);
@@ -9,6 +9,18 @@ use http_body_util::combinators::BoxBody;
 
     use super::SseService;
 
+    #[derive(serde::Deserialize)]
+    struct TeamJoin {
+        user: String,
+        team: String,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Wager {
+        user: String,
+        wager: f64,
+    }
+
     pub type Body = BoxBody<Bytes, Error>;
     
     fn full<T: Into<Bytes>>(chunk: T) -> BoxBody<Bytes, Error> {
@@ -34,11 +46,11 @@ use http_body_util::combinators::BoxBody;
                 let boxed_body = body
                     .map_err(|e|e.into())
                     .boxed();
-                return Ok(Response::from_parts(parts, boxed_body));
+                Ok(Response::from_parts(parts, boxed_body))
             },
             Err(e) => {
                 quiz_print!("Could not serve file: {}", e);
-                return not_found();
+                not_found()
             }
         }
     }
@@ -47,7 +59,7 @@ use http_body_util::combinators::BoxBody;
         let (send, receiver) = channel(1000);
         let body = StreamBody::new(ReceiverStream::new(receiver));
         let boxed_body = body
-            .map_err(|e| Error::from(e))
+            .map_err(Error::from)
             .boxed();
 
         sse.add_client(send).await;
@@ -69,17 +81,16 @@ use http_body_util::combinators::BoxBody;
     }
 
     pub async fn login_answer(state: QuizStateService, sse: SseService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
-        if let Some(username) = to_string(body).await {
-            if state.add_user(&username).await.is_ok() {
-                if let Some(e) = state.lobby().await {
-                    sse.send_event(e).await;
-                }
-                return Response::builder()
-                    .status(StatusCode::ACCEPTED)
-                    .header("Content-Type", "text/plain")
-                    .body(full(username))
-                    .into_result();
+        if let Some(username) = to_string(body).await
+            && state.add_user(&username).await.is_ok() {
+            if let Some(e) = state.lobby().await {
+                sse.send_event(e).await;
             }
+            return Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .header("Content-Type", "text/plain")
+                .body(full(username))
+                .into_result();
         }
         Response::builder()
             .status(StatusCode::BAD_REQUEST)
@@ -88,16 +99,43 @@ use http_body_util::combinators::BoxBody;
             .into_result()
     }
 
-    pub async fn relogin_answer(state: QuizStateService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
-        if let Some(username) = to_string(body).await {
-            if state.user_exists(&username).await {
-                return Response::builder()
-                    .status(StatusCode::ACCEPTED)
+    pub async fn join_team(state: QuizStateService, sse: SseService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
+        if let Some(join) = to_string(body).await {
+            let join: TeamJoin = serde_json::from_str(&join)?;
+            match state.join_team(&join.user, &join.team).await {
+                Ok(_) => {
+                    if let Some(e) = state.lobby().await {
+                        sse.send_event(e).await;
+                    }
+                    return Response::builder()
+                        .status(StatusCode::ACCEPTED)
+                        .header("Content-Type", "text/plain")
+                        .body(full(join.team))
+                        .into_result();
+                },
+                Err(e) => return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
                     .header("Content-Type", "text/plain")
-                    .body(full(username))
-                    .into_result();
+                    .body(full(e.to_string()))
+                    .into_result(),
             }
         }
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("Content-Type", "text/plain")
+            .body(full("Could not join team"))
+            .into_result()
+    }
+
+    pub async fn relogin_answer(state: QuizStateService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
+        if let Some(username) = to_string(body).await
+            && state.user_exists(&username).await {
+            return Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .header("Content-Type", "text/plain")
+                .body(full(username))
+                .into_result();
+        }
         Response::builder()
             .status(StatusCode::BAD_REQUEST)
             .header("Content-Type", "text/plain")
@@ -128,12 +166,63 @@ use http_body_util::combinators::BoxBody;
             .into_result()
     }
 
+    pub async fn buzz(state: QuizStateService, sse: SseService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
+        if let Some(username) = to_string(body).await {
+            match state.buzz(&username).await {
+                Ok(event) => {
+                    if let Some(e) = event {
+                        quiz_print!("`{}` has the turn to answer! Use `accept` or `reject`.", username);
+                        sse.send_event(e).await;
+                    }
+                    return Response::builder()
+                        .status(StatusCode::ACCEPTED)
+                        .header("Content-Type", "text/plain")
+                        .body(full(username))
+                        .into_result();
+                },
+                Err(err) => return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .header("Content-Type", "text/plain")
+                    .body(full(err.to_string()))
+                    .into_result(),
+            }
+        }
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("Content-Type", "text/plain")
+            .body(full("Buzz was not accepted"))
+            .into_result()
+    }
+
+    pub async fn place_wager(state: QuizStateService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
+        if let Some(wager) = to_string(body).await {
+            let wager: Wager = serde_json::from_str(&wager)?;
+            match state.place_wager(&wager.user, wager.wager).await {
+                Ok(_) => return Response::builder()
+                    .status(StatusCode::ACCEPTED)
+                    .header("Content-Type", "text/plain")
+                    .body(full(wager.wager.to_string()))
+                    .into_result(),
+                Err(err) => return Response::builder()
+                    .status(StatusCode::BAD_REQUEST)
+                    .header("Content-Type", "text/plain")
+                    .body(full(err.to_string()))
+                    .into_result(),
+            }
+        }
+        Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("Content-Type", "text/plain")
+            .body(full("Wager was not accepted"))
+            .into_result()
+    }
+
     pub async fn last_event(state: QuizStateService, sse: SseService, body: Full<Bytes>) -> QuizResult<Response<Body>> {
         if let Some(username) = to_string(body).await {
             if state.user_exists(&username).await {
                 let last_event_json = match sse.last_event().await {
                     None => "null".to_string(),
-                    Some(event) => event.to_string(),
+                    Some(event) => state.personalize(event, &username).await.to_string(),
                 };
                 return Response::builder()
                     .status(StatusCode::OK)
@@ -151,6 +240,25 @@ use http_body_util::combinators::BoxBody;
             .into_result()
     }
 
+    /// The html report, only for requests with the admin token in the query.
+    pub async fn report(state: QuizStateService, query: Option<&str>, admin_token: &str) -> QuizResult<Response<Body>> {
+        let authorized = query.is_some_and(|q| q.split('&').any(|p| p.strip_prefix("token=") == Some(admin_token)));
+        if !authorized {
+            return Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .header("Content-Type", "text/plain")
+                .body(full("403 FORBIDDEN"))
+                .into_result();
+        }
+        let report = state.report().await;
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .header("Cache-Control", "no-cache")
+            .body(full(report))
+            .into_result()
+    }
+
     async fn to_string(mut body: Full<Bytes>) -> Option<String> {
         let bytes = body.frame().await.unwrap().unwrap().into_data().unwrap();
         Some(std::str::from_utf8(bytes.as_ref()).ok()?.to_string())
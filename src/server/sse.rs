@@ -8,7 +8,7 @@ type Client = Sender<http::Result<Frame<Bytes>>>;
 
 pub enum SseJob {
     AddClient(Client),
-    SendEvent(Event),
+    SendEvent(Box<Event>),
     LastEvent(Return<Option<Event>>),
     Close,
 }
@@ -28,7 +28,7 @@ impl SseService {
     }
 
     pub async fn send_event(&self, event: Event) {
-        self.job_channel.send(SseJob::SendEvent(event)).await.expect("Send failed");
+        self.job_channel.send(SseJob::SendEvent(Box::new(event))).await.expect("Send failed");
     }
 
     pub async fn last_event(&self) -> Option<Event> {
@@ -66,7 +66,7 @@ impl SseState {
             if let Some(job) = job_receiver.recv().await {
                 match job {
                     SseJob::AddClient(client) => self.add_client(client),
-                    SseJob::SendEvent(event) => self.send_to_clients(event).await,
+                    SseJob::SendEvent(event) => self.send_to_clients(*event).await,
                     SseJob::LastEvent(callback) => callback.send(self.last_event()).expect("Failed returning last event."),
                     SseJob::Close => {
                         self.close().await;
@@ -83,7 +83,10 @@ impl SseState {
 
     async fn send_to_clients(&mut self, event: Event) {
         let event_json = event.to_string();
-        self.last_event = Some(event);
+        // buzzer updates only make sense on top of the question they belong to
+        if !matches!(event, Event::Buzzer{..}) {
+            self.last_event = Some(event);
+        }
         
         let bytes: Bytes = format!("data:{}\n\n", event_json).into();
         let mut sent = futures::future::join_all(self.clients.iter_mut().map(|client| {
@@ -1,13 +1,16 @@
 
+mod accept;
 mod config;
 mod status;
 mod question;
 mod owner;
+mod report;
 mod service;
 
-pub use config::{Config, get_config};
-pub use question::{Answer, AnswerType, Question, QuestionType};
-pub use status::{Event, QuizStatus, Ranking, Score};
+pub use config::{Config, ConfigFormat, TeamScoring, get_config, validate_config};
+pub use accept::AcceptedAnswers;
+pub use question::{Answer, AnswerType, GradeRange, OptionScoring, Question, QuestionType, Tolerance};
+pub use status::{AnswerResult, Distribution, Event, QuestionResult, QuizStatus, Ranking, Results, RoundInfo, RoundRanking, Score, UserResult};
 pub use service::QuizStateService;
 
 use std::path::PathBuf;
@@ -16,7 +19,7 @@ use tokio::sync::mpsc::channel;
 pub fn create_quiz_state(root: PathBuf, config: Config) -> QuizStateService {            
     let (job_sender, job_receiver) = channel(1000);
 
-    owner::create_quiz_state(root, config, job_receiver);
+    owner::create_quiz_state(root, config, job_sender.clone(), job_receiver);
 
     QuizStateService::new(job_sender)
 }
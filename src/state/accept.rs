@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+fn default_max_distance() -> usize {
+    2
+}
+
+fn strip_diacritics(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+/// Lowercases, strips diacritics and collapses whitespace.
+pub fn normalize(answer: &str) -> String {
+    strip_diacritics(answer)
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + if ca == *cb { 0 } else { 1 };
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(previous + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Compiles a pattern to match normalized answers: case-insensitive and without diacritics.
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&strip_diacritics(pattern)).case_insensitive(true).build()
+}
+
+/// Answers accepted for an open question.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptedAnswers {
+    #[serde(default)]
+    answers: Vec<String>,
+    /// Regular expressions matched against the normalized answer, ignoring case and diacritics.
+    #[serde(default)]
+    patterns: Vec<String>,
+    /// Answers within this edit distance of an accepted answer are probably correct.
+    #[serde(default = "default_max_distance")]
+    max_distance: usize,
+    /// The valid patterns, compiled on first use.
+    #[serde(skip)]
+    compiled: OnceLock<Vec<Regex>>,
+}
+
+impl Default for AcceptedAnswers {
+    fn default() -> Self {
+        AcceptedAnswers::new(Vec::new())
+    }
+}
+
+impl AcceptedAnswers {
+    pub fn new(answers: Vec<String>) -> Self {
+        AcceptedAnswers { answers, patterns: Vec::new(), max_distance: default_max_distance(), compiled: OnceLock::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty() && self.patterns.is_empty()
+    }
+
+    pub fn answers(&self) -> &Vec<String> {
+        &self.answers
+    }
+
+    pub fn patterns(&self) -> &Vec<String> {
+        &self.patterns
+    }
+
+    /// Patterns that are not valid regular expressions.
+    pub fn validate(&self) -> Vec<String> {
+        self.patterns.iter()
+            .filter_map(|p| compile(p).err().map(|e| format!("accepted pattern `{}` is invalid: {}", p, e)))
+            .collect()
+    }
+
+    pub fn matches(&self, answer: &str) -> bool {
+        let answer = normalize(answer);
+        self.answers.iter().any(|a| normalize(a) == answer)
+            || self.compiled().iter().any(|r| r.is_match(&answer))
+    }
+
+    fn compiled(&self) -> &Vec<Regex> {
+        self.compiled.get_or_init(|| self.patterns.iter().filter_map(|p| compile(p).ok()).collect())
+    }
+
+    pub fn is_near_miss(&self, answer: &str) -> bool {
+        let answer = normalize(answer);
+        !self.matches(&answer) && self.answers.iter()
+            .any(|a| edit_distance(&normalize(a), &answer) <= self.max_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_match_ignoring_case_whitespace_and_diacritics() {
+        let accept = AcceptedAnswers::new(vec!["Paris".into()]);
+        assert!(accept.matches("paris "));
+        assert!(accept.matches("  PARIS"));
+        assert!(accept.matches("Pâris"));
+        assert!(!accept.matches("Lyon"));
+    }
+
+    #[test]
+    fn near_misses_are_within_the_default_distance_of_two() {
+        let accept = AcceptedAnswers::new(vec!["Paris".into()]);
+        assert_eq!(accept.max_distance, 2);
+        assert!(accept.is_near_miss("Pariss"));
+        assert!(accept.is_near_miss("Parsi"));
+        assert!(!accept.is_near_miss("Paris"));
+        assert!(!accept.is_near_miss("Berlin"));
+    }
+
+    #[test]
+    fn patterns_match_the_normalized_answer() {
+        let accept = AcceptedAnswers { patterns: vec![r"^(the )?eiffel tower$".into()], ..AcceptedAnswers::default() };
+        assert!(accept.validate().is_empty());
+        assert!(accept.matches("The Eiffel  Tower"));
+        assert!(accept.matches("Eiffel tower"));
+        assert!(!accept.matches("tower"));
+        let broken = AcceptedAnswers { patterns: vec!["(".into()], ..AcceptedAnswers::default() };
+        assert_eq!(broken.validate().len(), 1);
+        assert!(!broken.matches("("));
+    }
+}
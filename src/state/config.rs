@@ -1,22 +1,342 @@
-use std::path::PathBuf;
-use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use rand::{rngs::StdRng, seq::IndexedRandom, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::error::QuizResult;
+use crate::error::{Error, QuizResult};
 
 use super::Question;
 
 
-#[derive(Debug, Serialize, Deserialize)]
+/// File names the quiz config is looked up by, in order.
+const CONFIG_FILES: [&str; 5] = ["quiz.config", "quiz.json", "quiz.yaml", "quiz.yml", "quiz.toml"];
+
+/// Format of a quiz config or pool file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ConfigFormat::Json),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            _ => Err(format!("Unknown config format: {} (use json, yaml or toml)", s)),
+        }
+    }
+}
+
+impl ConfigFormat {
+    /// The format of a file by its extension, `None` for other extensions such as `.config`.
+    pub fn of(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    /// Deserializes `data`, errors name the path of the value that failed, like `rounds[0].questions[2]`.
+    fn parse<T: DeserializeOwned>(self, data: &str) -> Result<T, String> {
+        fn describe<E: std::fmt::Display>(e: serde_path_to_error::Error<E>) -> String {
+            let (path, inner) = (e.path().to_string(), e.inner().to_string());
+            if path == "." || inner.starts_with(&path) { inner } else { format!("at {}: {}", path, inner) }
+        }
+        match self {
+            ConfigFormat::Json => serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_str(data)).map_err(describe),
+            ConfigFormat::Yaml => {
+                // enums are written as `Variant: value` like in json, instead of yaml tags
+                let mut track = serde_path_to_error::Track::new();
+                let deserializer = serde_path_to_error::Deserializer::new(serde_yaml::Deserializer::from_str(data), &mut track);
+                serde_yaml::with::singleton_map_recursive::deserialize(deserializer)
+                    .map_err(|e| describe(serde_path_to_error::Error::new(track.path(), e)))
+            },
+            ConfigFormat::Toml => serde_path_to_error::deserialize(toml::Deserializer::new(data)).map_err(describe),
+        }
+    }
+}
+
+/// Questions of a pool file, TOML files list them under `questions` as TOML has no top-level lists.
+#[derive(Deserialize)]
+struct TomlPool {
+    questions: Vec<Question>,
+}
+
+/// A quiz.config as it is written, with questions either listed directly or grouped in rounds.
+#[derive(Clone, Serialize, Deserialize)]
+struct ConfigFile {
+    title: String,
+    #[serde(default)]
+    questions: Vec<Question>,
+    #[serde(default)]
+    rounds: Vec<RoundFile>,
+    #[serde(default)]
+    teams: Option<Teams>,
+    #[serde(default)]
+    shuffle_options: bool,
+    /// Seed for drawing questions from pools, a random seed is picked when none is given.
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pools: BTreeMap<String, Pool>,
+    /// Questions drawn from pools, following the listed questions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    draw: Vec<Draw>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct RoundFile {
+    name: String,
+    #[serde(default)]
+    intro: Option<String>,
+    #[serde(default)]
+    questions: Vec<Question>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    draw: Vec<Draw>,
+}
+
+/// A bank of questions, listed directly or in a file relative to the quiz root.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Pool {
+    Questions(Vec<Question>),
+    File { file: PathBuf },
+}
+
+/// Draws `count` questions from a pool, optionally only of a category and difficulty.
+#[derive(Clone, Serialize, Deserialize)]
+struct Draw {
+    pool: String,
+    count: usize,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    difficulty: Option<String>,
+}
+
+impl ConfigFile {
+    /// Reads the quiz config in `root`, in the given format or the format of its extension, and its pools.
+    fn read(root: &Path, format: Option<ConfigFormat>) -> QuizResult<Self> {
+        let files = || CONFIG_FILES.iter().map(|name| root.join(name)).filter(|path| path.is_file());
+        // a file with the extension of a given format wins over `quiz.config`, which may hold any format
+        let path = files()
+            .find(|path| format.is_none() || ConfigFormat::of(path) == format)
+            .or_else(|| files().find(|path| ConfigFormat::of(path).is_none()))
+            .ok_or_else(|| Error::String(format!("No quiz config found, expected one of: {}", CONFIG_FILES.join(", "))))?;
+        Self::read_file(&path, format)
+    }
+
+    /// Reads the quiz config at `path` and its pools, relative to the directory of the config.
+    fn read_file(path: &Path, format: Option<ConfigFormat>) -> QuizResult<Self> {
+        let format = format.or(ConfigFormat::of(path)).unwrap_or(ConfigFormat::Json);
+        let data = std::fs::read_to_string(path)?;
+        let mut file: ConfigFile = format.parse(&data)
+            .map_err(|e| Error::String(format!("{:?} {}", path, e)))?;
+        file.load_pools(path.parent().unwrap_or(Path::new(".")), format)?;
+        Ok(file)
+    }
+
+    /// Reads the pools given as files, relative to the quiz root.
+    fn load_pools(&mut self, root: &Path, format: ConfigFormat) -> QuizResult<()> {
+        for pool in self.pools.values_mut() {
+            if let Pool::File{file} = pool {
+                let data = std::fs::read_to_string(root.join(&file))
+                    .map_err(|e| Error::String(format!("Could not read pool {:?}: {}", file, e)))?;
+                let questions = match ConfigFormat::of(file).unwrap_or(format) {
+                    ConfigFormat::Toml => ConfigFormat::Toml.parse::<TomlPool>(&data).map(|p| p.questions),
+                    format => format.parse(&data),
+                };
+                *pool = Pool::Questions(questions.map_err(|e| Error::String(format!("Pool {:?} {}", file, e)))?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draws questions from the pools, a question is drawn at most once.
+struct Drawer<'a> {
+    pools: &'a BTreeMap<String, Pool>,
+    rng: StdRng,
+    drawn: HashSet<(String, String)>,
+}
+
+impl Drawer<'_> {
+    fn draw(&mut self, draws: &[Draw]) -> Result<Vec<Question>, String> {
+        let mut questions = Vec::new();
+        for draw in draws {
+            let Some(Pool::Questions(pool)) = self.pools.get(&draw.pool) else {
+                return Err(format!("Unknown question pool: {}", draw.pool));
+            };
+            let candidates = pool.iter()
+                .filter(|q| draw.category.is_none() || q.category() == draw.category.as_ref())
+                .filter(|q| draw.difficulty.is_none() || q.difficulty() == draw.difficulty.as_ref())
+                .filter(|q| !self.drawn.contains(&(draw.pool.clone(), q.title().clone())))
+                .collect::<Vec<_>>();
+            if candidates.len() < draw.count {
+                return Err(format!("Cannot draw {} questions from pool {}, only {} left", draw.count, draw.pool, candidates.len()));
+            }
+            for question in candidates.choose_multiple(&mut self.rng, draw.count) {
+                self.drawn.insert((draw.pool.clone(), question.title().clone()));
+                questions.push((*question).clone());
+            }
+        }
+        Ok(questions)
+    }
+}
+
+/// Team mode settings, players play in teams when these are given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Teams {
+    /// Teams that exist from the start, more can be created in the lobby.
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    scoring: TeamScoring,
+}
+
+impl Teams {
+    pub fn names(&self) -> &Vec<String> {
+        &self.names
+    }
+
+    pub fn scoring(&self) -> TeamScoring {
+        self.scoring
+    }
+}
+
+/// How the scores of team members make up the team score.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum TeamScoring {
+    /// Sum of the members' scores.
+    #[default]
+    Sum,
+    /// Average of the members' scores.
+    Average,
+    /// One answer per team: an answer by one member counts for the whole team.
+    Shared,
+}
+
+#[derive(Debug, Clone)]
+pub struct Round {
+    name: String,
+    intro: Option<String>,
+    questions: Range<usize>,
+}
+
+impl Round {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn intro(&self) -> Option<&String> {
+        self.intro.as_ref()
+    }
+
+    /// Indices of the questions in this round.
+    pub fn questions(&self) -> Range<usize> {
+        self.questions.clone()
+    }
+}
+
+/// The quiz with all questions in one list. Questions of rounds follow the questions outside of rounds.
+/// Questions drawn from pools are part of the questions, so a backup keeps the same selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ConfigFile", into = "ConfigFile")]
 pub struct Config {
     title: String,
     questions: Vec<Question>,
+    rounds: Vec<Round>,
+    teams: Option<Teams>,
+    /// Shuffle the options of multi-choice and multi-option questions for every user.
+    shuffle_options: bool,
+    /// Seed the questions were drawn from pools with.
+    seed: u64,
 }
 
 impl Config {
-    pub fn from(root: &PathBuf) -> QuizResult<Self> {
-        let path = root.join("quiz.config");
-        let data = std::fs::read_to_string(path)?;
-        Ok(serde_json::from_str(&data)?)
+    /// Draws the questions from the pools and generates missing ids, without checking that ids are unique.
+    fn resolve(file: ConfigFile) -> Result<Self, String> {
+        let seed = file.seed.unwrap_or_else(rand::random);
+        let mut drawer = Drawer { pools: &file.pools, rng: StdRng::seed_from_u64(seed), drawn: HashSet::new() };
+        let mut questions = file.questions;
+        questions.extend(drawer.draw(&file.draw)?);
+        let mut rounds = Vec::new();
+        for round in file.rounds {
+            let start = questions.len();
+            questions.extend(round.questions);
+            questions.extend(drawer.draw(&round.draw)?);
+            rounds.push(Round { name: round.name, intro: round.intro, questions: start..questions.len() });
+        }
+        for (i, question) in questions.iter_mut().enumerate() {
+            question.generate_id(i);
+        }
+        Ok(Config { title: file.title, questions, rounds, teams: file.teams, shuffle_options: file.shuffle_options, seed })
+    }
+
+    /// Index of an earlier question with the same id as the question at `index`.
+    fn duplicate_id(&self, index: usize) -> Option<usize> {
+        let id = self.questions.get(index)?.id();
+        self.questions[..index].iter().position(|q| q.id() == id)
+    }
+}
+
+impl TryFrom<ConfigFile> for Config {
+    type Error = String;
+
+    fn try_from(file: ConfigFile) -> Result<Self, String> {
+        let config = Config::resolve(file)?;
+        if let Some(i) = config.questions.iter().position(Question::is_missing_grade_range) {
+            return Err(format!("question {} ({}): grade_range is missing", i, config.questions[i].id()));
+        }
+        if let Some((i, first)) = (0..config.questions.len()).find_map(|i| config.duplicate_id(i).map(|first| (i, first))) {
+            return Err(format!("Duplicate question id: {} (questions {} and {})", config.questions[i].id(), first, i));
+        }
+        Ok(config)
+    }
+}
+
+impl From<Config> for ConfigFile {
+    fn from(config: Config) -> Self {
+        let first_round = config.rounds.first().map(|r| r.questions.start).unwrap_or(config.questions.len());
+        ConfigFile {
+            title: config.title,
+            questions: config.questions[..first_round].to_vec(),
+            rounds: config.rounds.into_iter().map(|round| RoundFile {
+                questions: config.questions[round.questions].to_vec(),
+                name: round.name,
+                intro: round.intro,
+                draw: Vec::new(),
+            }).collect(),
+            teams: config.teams,
+            shuffle_options: config.shuffle_options,
+            seed: Some(config.seed),
+            pools: BTreeMap::new(),
+            draw: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// A quiz of the given questions without rounds, as made by the importers.
+    pub fn new(title: String, questions: Vec<Question>) -> Result<Self, String> {
+        Config::try_from(ConfigFile {
+            title, questions,
+            rounds: Vec::new(), teams: None, shuffle_options: false, seed: None,
+            pools: BTreeMap::new(), draw: Vec::new(),
+        })
+    }
+
+    pub fn from(root: &Path, format: Option<ConfigFormat>) -> QuizResult<Self> {
+        Config::try_from(ConfigFile::read(root, format)?).map_err(Error::String)
+    }
+
+    /// Reads a quiz config file by its path instead of the quiz root.
+    pub fn from_file(path: &Path) -> QuizResult<Self> {
+        Config::try_from(ConfigFile::read_file(path, None)?).map_err(Error::String)
     }
 
     pub fn title(&self) -> &str {
@@ -30,19 +350,96 @@ impl Config {
     pub fn questions(&self) -> &Vec<Question> {
         &self.questions
     }
+
+    /// Whether the options of a question are shuffled for every user.
+    pub fn shuffles_options(&self, index: usize) -> bool {
+        self.shuffle_options && self.questions.get(index).is_some_and(|q| q.has_shuffleable_options())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn teams(&self) -> Option<&Teams> {
+        self.teams.as_ref()
+    }
+
+    pub fn rounds(&self) -> &Vec<Round> {
+        &self.rounds
+    }
+
+    /// The number and round of a question, if it is part of a round.
+    pub fn round_of(&self, index: usize) -> Option<(usize,&Round)> {
+        self.rounds.iter().enumerate().find(|(_,r)| r.questions.contains(&index))
+    }
     
-    pub fn question(&self, title: &str) -> Option<(usize,&Question)> {
-        self.questions.iter().enumerate().filter(|(_,q)| q.title() == title).next()
+    pub fn question(&self, id: &str) -> Option<(usize,&Question)> {
+        self.questions.iter().enumerate().find(|(_,q)| q.id() == id)
+    }
+}
+
+/// Checks the quiz config under `root`, returns all problems found with the numbers of the questions.
+pub fn validate_config(root: &str, format: Option<ConfigFormat>) -> Result<Vec<String>, String> {
+    let root = std::fs::canonicalize(root)
+        .map_err(|_| format!("Could not find quiz root: {}", root))?;
+    let file = ConfigFile::read(&root, format)
+        .map_err(|e| format!("Could not read quiz config: {}", e))?;
+
+    let mut problems = Vec::new();
+    // without a seed the draw below differs from the one when playing, so pools are checked in full
+    let listed = file.questions.iter().chain(file.rounds.iter().flat_map(|r| &r.questions))
+        .map(|q| q.title())
+        .collect::<HashSet<_>>();
+    for (name, pool) in &file.pools {
+        if let Pool::Questions(questions) = pool {
+            for (i, question) in questions.iter().enumerate() {
+                let mut question_problems = question.validate();
+                if listed.contains(question.title()) {
+                    question_problems.push("title is the same as a listed question".to_string());
+                }
+                problems.extend(question_problems.into_iter()
+                    .map(|p| format!("pool {} question {} ({}): {}", name, i, question.title(), p)));
+            }
+        }
+    }
+    let draws = !file.draw.is_empty() || file.rounds.iter().any(|r| !r.draw.is_empty());
+    if draws && file.seed.is_none() {
+        problems.push("questions are drawn without a seed, every start draws a different selection than checked here".to_string());
+    }
+    let config = match Config::resolve(file) {
+        Ok(config) => config,
+        Err(e) => {
+            problems.push(e);
+            return Ok(problems);
+        }
+    };
+    for (i, question) in config.questions.iter().enumerate() {
+        let mut question_problems = question.validate();
+        if let Some(image) = question.image() && !root.join(image.strip_prefix("/").unwrap_or(image)).is_file() {
+            question_problems.push(format!("image {:?} does not exist under the quiz root", image));
+        }
+        if let Some((first,_)) = config.questions.iter().enumerate().find(|(_,q)| q.title() == question.title()) && first < i {
+            question_problems.push(format!("title is the same as question {}", first));
+        }
+        if let Some(first) = config.duplicate_id(i) {
+            question_problems.push(format!("id is the same as question {}", first));
+        }
+        problems.extend(question_problems.into_iter()
+            .map(|p| format!("question {} ({}): {}", i, question.id(), p)));
+    }
+    if config.questions.is_empty() {
+        problems.push("there are no questions".to_string());
     }
+    Ok(problems)
 }
 
-pub fn get_config(root: &str) -> Result<(PathBuf, Config),String> {
+pub fn get_config(root: &str, format: Option<ConfigFormat>) -> Result<(PathBuf, Config),String> {
 	let path = root.to_string();
 	std::fs::canonicalize(&path)
 		.map_err(|_| format!("Could not find quiz root: {}\n", path))
 		.and_then(|root|{
-			Config::from(&root)
-				.map_err(|e| format!("Could not import quiz.config file: {}\n", e))
+			Config::from(&root, format)
+				.map_err(|e| format!("Could not import quiz config: {}\n", e))
 				.map(|config| (root, config))
 		})
 }
\ No newline at end of file
@@ -1,20 +1,26 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, ops::Range, path::PathBuf, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
 
 use crate::error::{Error, QuizResult};
 
-use super::{service::QuizStateJob, Answer, AnswerType, Config, Event, Question, QuestionType, QuizStatus, Ranking, Score};
+use super::{question::round_score, report::{self, QuestionReport}, service::{QuestionAnswers, QuizStateJob, TeamMembers}, Answer, AnswerType, Config, TeamScoring, Distribution, Event, Question, QuestionType, QuizStatus, Ranking, Results, QuestionResult, UserResult, AnswerResult, RoundInfo, RoundRanking, Score};
 
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{Receiver, Sender};
 
-pub(super) fn create_quiz_state(root: PathBuf, config: Config, job_receiver: Receiver<QuizStateJob>) {            
+pub(super) fn create_quiz_state(root: PathBuf, config: Config, job_sender: Sender<QuizStateJob>, job_receiver: Receiver<QuizStateJob>) {            
     tokio::task::spawn(async move {
         let owner = QuizStateOwner{
             root,
+            job_sender,
+            opened: 0,
+            opened_at: Instant::now(),
+            buzzers: Vec::new(),
             state: QuizState {
-                config,
                 status: QuizStatus::Lobby,
                 users: HashMap::new(),
+                teams: config.teams().map(|t| t.names().clone()).unwrap_or_default(),
+                config,
             }
         };
 
@@ -22,15 +28,46 @@ pub(super) fn create_quiz_state(root: PathBuf, config: Config, job_receiver: Rec
     });
 }
 
+/// Answers, times, wagers and permutations are stored by question id.
 #[derive(Debug, Serialize, Deserialize)]
 struct UserState {
     answers: HashMap<String,(AnswerType, Score)>,
-    bonus_score: i32,
+    /// Seconds between opening a question and submitting the answer.
+    #[serde(default)]
+    answer_times: HashMap<String, f64>,
+    bonus_score: f64,
+    #[serde(default)]
+    team: Option<String>,
+    /// Wagers placed on wager questions.
+    #[serde(default)]
+    wagers: HashMap<String, f64>,
+    /// Order in which the options of a question are shown to the user.
+    #[serde(default)]
+    permutations: HashMap<String, Vec<usize>>,
 }
 
 impl UserState {
     fn new() -> Self {
-        UserState { answers: HashMap::new(), bonus_score: 0 }
+        UserState { answers: HashMap::new(), answer_times: HashMap::new(), bonus_score: 0.0, team: None, wagers: HashMap::new(), permutations: HashMap::new() }
+    }
+
+    /// Stores answers by question id that were stored by title, as in backups from before question ids.
+    fn migrate_title_keys(&mut self, config: &Config) {
+        rekey_titles(&mut self.answers, config);
+        rekey_titles(&mut self.answer_times, config);
+        rekey_titles(&mut self.wagers, config);
+        rekey_titles(&mut self.permutations, config);
+    }
+}
+
+fn rekey_titles<T>(map: &mut HashMap<String, T>, config: &Config) {
+    let titles: Vec<String> = map.keys().filter(|key| config.question(key).is_none()).cloned().collect();
+    for title in titles {
+        if let Some(question) = config.questions().iter().find(|q| *q.title() == title)
+            && let Some(value) = map.remove(&title)
+        {
+            map.insert(question.id().clone(), value);
+        }
     }
 }
 
@@ -39,11 +76,20 @@ struct QuizState {
     config: Config,
     status: QuizStatus,
     users: HashMap<String, UserState>,
+    #[serde(default)]
+    teams: Vec<String>,
 }
 
 #[derive(Debug)]
 struct QuizStateOwner {
     root: PathBuf,
+    /// Used by timers to send jobs back to the owner.
+    job_sender: Sender<QuizStateJob>,
+    /// Counts how many times a question was opened, so timers of earlier openings can be ignored.
+    opened: usize,
+    opened_at: Instant,
+    /// Users in the order they buzzed on the open buzzer question.
+    buzzers: Vec<String>,
     state: QuizState,
 }
 
@@ -60,18 +106,30 @@ impl QuizStateOwner {
                     QuizStateJob::UserExists(username, sender)                    => sender.send(self.user_exists(&username)).unwrap(),
                     QuizStateJob::RemoveUser(username, sender)       => sender.send(self.remove_user(username)).unwrap(),
                     QuizStateJob::AddUser(username, sender)          => sender.send(self.add_user(username)).unwrap(),
+                    QuizStateJob::JoinTeam(username, team, sender)      => sender.send(self.join_team(username, team)).unwrap(),
+                    QuizStateJob::Teams(sender)                                  => sender.send(self.teams()).unwrap(),
                     QuizStateJob::Lobby(sender)                                  => sender.send(self.lobby()).unwrap(),
                     QuizStateJob::Questions(sender)                => sender.send(self.questions()).unwrap(),
                     QuizStateJob::Question(index, sender)              => sender.send(self.question(index)).unwrap(),
+                    QuizStateJob::RoundOf(index, sender)              => sender.send(self.round_of(index)).unwrap(),
                     QuizStateJob::Ranking(sender)                                      => sender.send(self.ranking()).unwrap(),
+                    QuizStateJob::Results(sender)                                      => sender.send(self.results()).unwrap(),
+                    QuizStateJob::Report(sender)                                       => sender.send(self.report()).unwrap(),
                     QuizStateJob::UsersNoAnswer(sender)                            => sender.send(self.no_answer_users()).unwrap(),
                     QuizStateJob::UngradedAnswers(sender)                           => sender.send(self.ungraded_answers()).unwrap(),
                     QuizStateJob::Answers(index, sender)    => sender.send(self.answers(index)).unwrap(),
-                    QuizStateJob::UpdateGrade(user, question_title, grade)       => self.update_grade(user, question_title, grade),
+                    QuizStateJob::Distribution(index, sender)                => sender.send(self.distribution(index)).unwrap(),
+                    QuizStateJob::UpdateGrade(user, question_id, grade)       => self.update_grade(user, question_id, grade),
                     QuizStateJob::Start(sender)                                  => sender.send(self.start()).unwrap(),
                     QuizStateJob::Next(sender)                                   => sender.send(self.next()).unwrap(),
                     QuizStateJob::LockQuestion                                                          => self.lock_question(),
+                    QuizStateJob::TimeUp(index, opened)                                     => self.time_up(index, opened),
                     QuizStateJob::Redo(index, sender)                     => sender.send(self.redo(index)).unwrap(),
+                    QuizStateJob::Buzz(username, sender)                 => sender.send(self.buzz(username)).unwrap(),
+                    QuizStateJob::AcceptBuzz(sender)                => sender.send(self.accept_buzz()).unwrap(),
+                    QuizStateJob::RejectBuzz(sender)                => sender.send(self.reject_buzz()).unwrap(),
+                    QuizStateJob::Wager(username, wager, sender)    => sender.send(self.place_wager(username, wager)).unwrap(),
+                    QuizStateJob::Personalize(event, username, sender)  => sender.send(self.personalize(event, username)).unwrap(),
                     QuizStateJob::SubmitAnswer(answer, sender)  => sender.send(self.submit_answer(answer)).unwrap(),
                     QuizStateJob::Bonus(username, bonus, sender)=> sender.send(self.add_bonus(username, bonus)).unwrap(),
                     QuizStateJob::Backup(path, sender)              => sender.send(self.backup(&path)).unwrap(),
@@ -86,7 +144,7 @@ impl QuizStateOwner {
     }
 
     pub fn title(&self) -> &str {
-        &self.state.config.title()
+        self.state.config.title()
     }
     
     pub fn status(&self) -> &QuizStatus {
@@ -97,7 +155,7 @@ impl QuizStateOwner {
         self.state.users.len()
     }
 
-    pub fn users(&self) -> Vec<(String, i32)> {
+    pub fn users(&self) -> Vec<(String, f64)> {
         self.state.users.iter().map(|(username, data)|{
                     (username.clone(), data.bonus_score)
                 })
@@ -122,9 +180,47 @@ impl QuizStateOwner {
         } else { Err(Error::Other) }
     }
     
+    pub fn join_team(&mut self, username: String, team: String) -> QuizResult<()> {
+        if self.state.config.teams().is_none() {
+            return Err(Error::String("This quiz is not played in teams".into()));
+        }
+        if !self.status().is_lobby() {
+            return Err(Error::String("Teams can only be joined in the lobby".into()));
+        }
+        let team = team.trim().to_string();
+        if team.is_empty() {
+            return Err(Error::String("Invalid team name".into()));
+        }
+        let Some(user_state) = self.state.users.get_mut(&username) else {
+            return Err(Error::String(format!("User does not exist: `{}`", username)));
+        };
+        user_state.team = Some(team.clone());
+        if !self.state.teams.contains(&team) {
+            self.state.teams.push(team);
+        }
+        Ok(())
+    }
+
+    /// Teams and their members, if players play in teams.
+    pub fn teams(&self) -> Option<TeamMembers> {
+        self.state.config.teams()?;
+        Some(self.state.teams.iter().map(|team| {
+            let mut members: Vec<_> = self.state.users.iter()
+                .filter(|(_,u)| u.team.as_ref() == Some(team))
+                .map(|(user,_)| user.clone())
+                .collect();
+            members.sort();
+            (team.clone(), members)
+        }).collect())
+    }
+
+    fn lobby_event(&self) -> Event {
+        Event::Lobby{users:self.state.users.keys().cloned().collect(), teams:self.teams()}
+    }
+
     pub fn lobby(&self) -> Option<Event> {
         match self.status() {
-            QuizStatus::Lobby => Some(Event::Lobby{users:self.state.users.keys().map(|s|s.clone()).collect()}),
+            QuizStatus::Lobby => Some(self.lobby_event()),
             _ => None
         }
     }
@@ -139,29 +235,159 @@ impl QuizStateOwner {
         self.state.config.questions().get(index).cloned()
     }
     
-    pub fn ranking(&self) -> Ranking {
-        let max_score = self.state.config.questions().iter().fold(0,|acc,q| acc + q.max_score());
+    fn round_of(&self, index: usize) -> Option<String> {
+        self.state.config.round_of(index).map(|(_,r)| r.name().clone())
+    }
+
+    /// Maximum score and sorted scores of all users over a range of questions.
+    fn scores(&self, questions: Range<usize>, with_bonus: bool) -> (f64, Vec<(String, f64)>) {
+        let first = questions.start;
+        let questions = &self.state.config.questions()[questions];
+        let max_score = questions.iter()
+            .filter(|q| q.is_scored() && !q.is_wager())
+            .fold(0.0,|acc,q| round_score(acc + q.max_score()));
         let mut scores: Vec<_> = self.state.users.iter().map(|(user, user_state)|{
-            let score = self.state.config.questions().iter().fold(0,|acc, q| {
-                match user_state.answers.get(q.title()) {
-                    Some((_,Score::Grade(s))) => acc + s,
+            let score = questions.iter().enumerate().fold(0.0,|acc, (i, q)| {
+                match user_state.answers.get(q.id()) {
+                    Some((_,Score::Ungraded)) => acc,
+                    Some((_,Score::Grade(s))) if !q.is_wager() => acc + s,
+                    // a placed wager is lost without a fully correct answer, also when not answering at all
+                    answer if q.is_wager() && (answer.is_some() || self.is_closed(first + i)) => {
+                        let wager = user_state.wagers.get(q.id()).copied().unwrap_or_default();
+                        match answer {
+                            Some((_,Score::Grade(s))) if *s >= q.max_score() => acc + wager,
+                            _ => acc - wager,
+                        }
+                    },
                     _ => acc
                 }
             });
-            (user.clone(), score as i32 + user_state.bonus_score)
+            let bonus = if with_bonus { user_state.bonus_score } else { 0.0 };
+            (user.clone(), round_score(score + bonus))
         }).collect();
-        scores.sort_by(|(_,a), (_,b)| b.cmp(a));
-        Ranking{max_score,scores}
+        scores.sort_by(|(_,a), (_,b)| b.total_cmp(a));
+        (max_score, scores)
+    }
+
+    pub fn ranking(&self) -> Ranking {
+        let (max_score, scores) = self.scores(0..self.state.config.question_count(), true);
+        let rounds = self.state.config.rounds().iter()
+            .filter(|r| r.questions().start < self.opened_questions())
+            .map(|r| {
+                let (max_score, scores) = self.scores(r.questions(), false);
+                RoundRanking { name: r.name().clone(), max_score, scores }
+            })
+            .collect();
+        let teams = self.team_scores(&scores);
+        Ranking{max_score,scores,rounds,teams}
+    }
+
+    pub fn results(&self) -> Results {
+        let questions = self.state.config.questions();
+        let Ranking{max_score, scores, ..} = self.ranking();
+        let mut users: Vec<UserResult> = Vec::with_capacity(scores.len());
+        for (i, (user, total)) in scores.into_iter().enumerate() {
+            let Some(user_state) = self.state.users.get(&user) else { continue };
+            let rank = match users.last() {
+                Some(prev) if prev.total == total => prev.rank,
+                _ => i + 1,
+            };
+            let answers = questions.iter()
+                .map(|q| user_state.answers.get(q.id()).map(|(answer, grade)| AnswerResult {
+                    answer: q.get_answer_string(answer),
+                    grade: *grade,
+                }))
+                .collect();
+            users.push(UserResult { rank, user, team: user_state.team.clone(), answers, bonus: user_state.bonus_score, total });
+        }
+        let questions = questions.iter()
+            .map(|q| QuestionResult {
+                id: q.id().clone(),
+                title: q.title().clone(),
+                max_score: if q.is_scored() && !q.is_wager() { q.max_score() } else { 0.0 },
+            })
+            .collect();
+        Results { title: self.title().into(), max_score, questions, users }
+    }
+
+    /// Html report of the ranking and all questions opened so far.
+    pub fn report(&self) -> String {
+        let results = self.results();
+        let teams = self.ranking().teams;
+        let questions: Vec<_> = self.state.config.questions()[0..self.opened_questions()].iter()
+            .enumerate()
+            .map(|(index, q)| {
+                let mut answers: Vec<_> = if matches!(q.type_spec(), QuestionType::Open) {
+                    self.state.users.iter()
+                        .filter_map(|(user, user_state)| user_state.answers.get(q.id())
+                            .map(|(answer, score)| (user.clone(), q.get_answer_string(answer), *score)))
+                        .collect()
+                } else { Vec::new() };
+                answers.sort_by(|(a,..), (b,..)| a.cmp(b));
+                QuestionReport {
+                    title: q.title().clone(),
+                    max_score: q.max_score(),
+                    expected: q.type_spec().expected_answer(),
+                    accepted: q.accept().answers().iter().chain(q.accept().patterns()).cloned().collect(),
+                    image: q.image().and_then(|image| report::inline_image(&self.root, image)),
+                    distribution: self.distribution(index).map(|d| d.counts),
+                    answers,
+                }
+            })
+            .collect();
+        report::render(&results, &teams, &questions)
+    }
+
+    fn team_scores(&self, scores: &[(String, f64)]) -> Vec<(String, f64)> {
+        let Some(scoring) = self.state.config.teams().map(|t| t.scoring()) else {
+            return Vec::new();
+        };
+        let mut teams: Vec<_> = self.teams().unwrap_or_default().into_iter()
+            .filter(|(_,members)| !members.is_empty())
+            .map(|(team, members)| {
+                let total = scores.iter()
+                    .filter(|(user,_)| members.contains(user))
+                    .fold(0.0, |acc, (_,score)| acc + score);
+                let score = match scoring {
+                    TeamScoring::Sum => round_score(total),
+                    TeamScoring::Average | TeamScoring::Shared => round_score(total / members.len() as f64),
+                };
+                (team, score)
+            })
+            .collect();
+        teams.sort_by(|(_,a), (_,b)| b.total_cmp(a));
+        teams
+    }
+
+    /// Whether no more answers are accepted for a question, because it is locked or already past.
+    fn is_closed(&self, index: usize) -> bool {
+        match self.status() {
+            QuizStatus::Lobby => false,
+            QuizStatus::Wager{id} => index < *id,
+            QuizStatus::Question{id,locked} => index < *id || (index == *id && *locked),
+            QuizStatus::Done => true,
+        }
+    }
+
+    /// Number of questions that have been opened, including one in the wager phase.
+    fn opened_questions(&self) -> usize {
+        match self.status() {
+            QuizStatus::Lobby => 0,
+            QuizStatus::Wager{id} => id+1,
+            QuizStatus::Question{id,..} => id+1,
+            QuizStatus::Done => self.state.config.question_count(),
+        }
     }
 
     pub fn no_answer_users(&self) -> Vec<String> {
         let cur_q = match self.status() {
             QuizStatus::Question{id,..} => *id,
+            QuizStatus::Wager{id} => return self.no_wager_users(*id),
             _ => return Vec::new()
         };
         if let Some(question) = self.state.config.questions().get(cur_q) {
             self.state.users.iter().filter(|(_,user_state)|{
-                user_state.answers.get(question.title()).is_none()
+                !user_state.answers.contains_key(question.id())
             })
             .map(|(u,_)| u.to_string())
             .collect()
@@ -169,41 +395,82 @@ impl QuizStateOwner {
         else { Vec::new() }
     }
 
+    fn no_wager_users(&self, index: usize) -> Vec<String> {
+        let Some(question) = self.state.config.questions().get(index) else { return Vec::new() };
+        self.state.users.iter()
+            .filter(|(_,user_state)| !user_state.wagers.contains_key(question.id()))
+            .map(|(u,_)| u.to_string())
+            .collect()
+    }
+
     pub fn ungraded_answers(&self) -> Vec<usize> {
-        let cur_q = match self.status() {
-            QuizStatus::Lobby => return Vec::new(),
-            QuizStatus::Question{id,..} => *id,
-            QuizStatus::Done => self.state.config.question_count()-1
+        let answered = match self.status() {
+            QuizStatus::Wager{id} => *id,
+            _ => self.opened_questions()
         };
-        self.state.config.questions()[0..=cur_q].iter()
+        self.state.config.questions()[0..answered].iter()
                 .enumerate()
                 .filter(|(_,q)|{
-                    self.state.users.values().fold(false, |acc, user_state|{
-                        acc | match user_state.answers.get(q.title()).map(|(_,s)|s.is_ungraded()) {
-                            Some(u) => u,
-                            None => false
-                        }
+                    q.is_scored() && self.state.users.values().fold(false, |acc, user_state|{
+                        acc | user_state.answers.get(q.id()).is_some_and(|(_,s)|s.is_ungraded())
                     })
                 })
                 .map(|(i,_)|i)
                 .collect()
     }
 
-    pub fn answers(&self, index: usize) 
-        -> Option<(HashMap<String,(String,Score)>,std::ops::RangeInclusive<usize>)>
-	{
+    /// Answers of all users to a question, a shared team answer is only listed for its first member.
+    pub fn answers(&self, index: usize) -> Option<QuestionAnswers> {
         let question = self.state.config.questions().get(index)?;
-        Some((self.state.users.iter().map(|(user,user_state)|{
-            if let Some((answer,score)) = user_state.answers.get(question.title()) 
-                { (user.clone(),(question.get_answer_string(&answer),*score)) }
-            else { (user.clone(),("".to_string(),Score::Ungraded)) }
+        let shared_earlier = |user: &String| self.teammates(user).iter().any(|teammate| {
+            teammate < user && self.state.users.get(teammate).is_some_and(|u| u.answers.contains_key(question.id()))
+        });
+        Some((self.state.users.iter().filter(|(user,_)| !shared_earlier(user)).map(|(user,user_state)|{
+            let time = user_state.answer_times.get(question.id()).copied();
+            if let Some((answer,score)) = user_state.answers.get(question.id()) 
+                { (user.clone(),(question.get_answer_string(answer),*score,time)) }
+            else { (user.clone(),("".to_string(),Score::Ungraded,None)) }
         }).collect(),question.grade_range().range()))
     }
 
-    pub fn update_grade(&mut self, user: String, question_title: String, grade: usize) {
-        self.state.users.get_mut(&user)
-            .and_then(|user_state| user_state.answers.get_mut(&question_title))
-            .map(|(_,score)| *score = Score::Grade(grade));
+    pub fn distribution(&self, index: usize) -> Option<Distribution> {
+        let question = self.state.config.questions().get(index)?;
+        let mut counts = vec![0; question.options()?.len()];
+        for (answer,_) in self.state.users.values().filter_map(|u| u.answers.get(question.id())) {
+            for option in question.chosen_options(answer) {
+                if let Some(count) = counts.get_mut(option) {
+                    *count += 1;
+                }
+            }
+        }
+        Some(Distribution {
+            title: question.title().clone(),
+            counts: question.options()?.iter().cloned().zip(counts).collect(),
+        })
+    }
+
+    /// Grades the answer of a user, and of the teammates that share it, lowered by the time the
+    /// answer took when the question uses speed scoring.
+    pub fn update_grade(&mut self, user: String, question_id: String, grade: f64) {
+        let grade = round_score(grade);
+        let mut users = self.teammates(&user);
+        users.push(user);
+        let question = self.state.config.question(&question_id).map(|(_,q)| q);
+        for user in users {
+            let Some(user_state) = self.state.users.get_mut(&user) else { continue };
+            let time = user_state.answer_times.get(&question_id).copied().unwrap_or_default();
+            if let Some((_,score)) = user_state.answers.get_mut(&question_id) {
+                *score = Score::Grade(question.map_or(grade, |q| q.speed_score(grade, Duration::from_secs_f64(time))));
+            }
+        }
+    }
+
+    fn round_info(&self, index: usize) -> Option<RoundInfo> {
+        self.state.config.round_of(index).map(|(id, r)| RoundInfo {
+            id, total: self.state.config.rounds().len(),
+            name: r.name().clone(),
+            intro: if r.questions().start == index { r.intro().cloned() } else { None },
+        })
     }
 
     fn question_event(&self, index: usize) -> Option<Event> {
@@ -214,101 +481,295 @@ impl QuizStateOwner {
             self.question(index).map(|q|{
                 Event::Question {
                     title: q.title().clone(), 
+                    question_id: q.id().clone(),
                     id:index, total:question_count,
                     image: q.image().and_then(|p| p.to_str().map(|s| s.to_string())), 
                     question_type: q.type_spec().into(),
+                    round: self.round_info(index),
+                    buzzer: q.is_buzzer(),
+                    shuffled: self.state.config.shuffles_options(index),
+                    deadline: None,
                 }
             })
         }
     }
 
-    fn answer_string(&self, answer: &Answer) -> String {
-        match self.state.config
-            .question(answer.question())
-            .map(|(_,q)| {
-                q.get_answer_string(answer.answer())
-            })
-        {
-            Some(s) => s,
-            None => "".into()
+    fn wager_event(&self, index: usize) -> Event {
+        Event::Wager {
+            id: index, total: self.state.config.question_count(),
+            round: self.round_info(index),
         }
     }
 
     pub fn start(&mut self) -> Option<Event> {
         match &self.state.status {
-            QuizStatus::Lobby => {
-                self.question_event(0).map(|e|{
-                    self.state.status = QuizStatus::Question{id:0,locked:false};
-                    e
-                })
-            },
+            QuizStatus::Lobby => self.open_question(0),
             _ => None
         }
     }
     
     pub fn next(&mut self) -> Option<Event> {
         match self.state.status {
+            QuizStatus::Wager{id} => self.reveal_question(id),
             QuizStatus::Question{id,..} => {
-                self.question_event(id+1).map(|e|{
-                    match e {
-                        Event::Question{..} => self.state.status = QuizStatus::Question{id:id+1,locked:false},
-                        Event::Finished => self.state.status = QuizStatus::Done,
-                        _ => ()
+                self.score_closed_question(id);
+                self.open_question(id+1).inspect(|e|{
+                    if let Event::Finished = e {
+                        self.state.status = QuizStatus::Done;
                     }
                     self.backup(&self.root.join(".backup_quiz")).unwrap();
-                    e
                 })
             },
             _ => None
         }
     }
     
-    pub fn lock_question(&mut self) {
-        match &mut self.state.status {
-            QuizStatus::Question{locked,..} => *locked = true,
+    /// Opens a question, starting with the wager phase for wager questions.
+    fn open_question(&mut self, index: usize) -> Option<Event> {
+        match self.state.config.questions().get(index) {
+            Some(question) if question.is_wager() => {
+                self.state.status = QuizStatus::Wager{id:index};
+                Some(self.wager_event(index))
+            },
+            _ => self.reveal_question(index)
+        }
+    }
+
+    /// Sets the status to a question and starts its timer if it has a time limit.
+    fn reveal_question(&mut self, index: usize) -> Option<Event> {
+        let mut event = self.question_event(index)?;
+        if let Event::Question{deadline,..} = &mut event {
+            self.state.status = QuizStatus::Question{id:index,locked:false};
+            self.opened += 1;
+            self.opened_at = Instant::now();
+            self.buzzers.clear();
+            if let Some(limit) = self.state.config.questions()[index].time_limit() {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                *deadline = Some((now + limit).as_millis() as u64);
+
+                let job_sender = self.job_sender.clone();
+                let opened = self.opened;
+                tokio::task::spawn(async move {
+                    tokio::time::sleep(limit).await;
+                    job_sender.send(QuizStateJob::TimeUp(index, opened)).await.expect("Send failed");
+                });
+            }
+        }
+        Some(event)
+    }
+
+    fn time_up(&mut self, index: usize, opened: usize) {
+        match self.state.status {
+            QuizStatus::Question{id,locked:false} if id == index && opened == self.opened => {
+                self.lock_question();
+                quiz_print!("Time is up, question {} is locked.", index);
+            },
             _ => ()
         }
     }
+
+    pub fn lock_question(&mut self) {
+        if let QuizStatus::Question{id,locked} = &mut self.state.status {
+            *locked = true;
+            let id = *id;
+            self.score_closed_question(id);
+        }
+    }
+
+    fn score_closed_question(&mut self, index: usize) {
+        let Some(question) = self.state.config.questions().get(index) else { return };
+        if !question.scored_on_lock() {
+            return;
+        }
+        let (users, answers): (Vec<_>, Vec<_>) = self.state.users.iter()
+            .filter_map(|(user, user_state)| {
+                user_state.answers.get(question.id()).map(|(answer,_)| (user.clone(), answer))
+            })
+            .unzip();
+        let scores = question.calculate_scores(&answers);
+        let question_id = question.id().clone();
+        for (user, score) in users.into_iter().zip(scores) {
+            if let Some(user_state) = self.state.users.get_mut(&user) {
+                let time = user_state.answer_times.get(&question_id).copied().unwrap_or_default();
+                if let Some((_,s)) = user_state.answers.get_mut(&question_id) {
+                    *s = score.map(|s| question.speed_score(s, Duration::from_secs_f64(time))).into();
+                }
+            }
+        }
+    }
     
     pub fn redo(&mut self, index: usize) -> Option<Event> {
         if index >= self.state.config.question_count() {
             None
         } else {
-            self.question_event(index).map(|e|{
-                    match e {
-                        Event::Question{..} => self.state.status = QuizStatus::Question{id:index,locked:false},
-                        _ => ()
-                    }
-					e
-                })
+            self.open_question(index)
         }
     }
 
     pub fn submit_answer(&mut self, answer: Answer) -> Result<String,String> {
-        let question_title = answer.question().clone();
+        let question_id = answer.question_id().clone();
         if let (Some(user_state),Some((index,question))) =
-            (self.state.users.get_mut(answer.user()), self.state.config.question(&question_title)) 
+            (self.state.users.get_mut(answer.user()), self.state.config.question(&question_id)) 
         {
             match self.state.status {
                 QuizStatus::Question{id,locked} if id == index => {
                     if locked {
                         return Err("Could not submit answer: question is locked.".into());
                     }
-                    let answer_type = answer.answer().clone();
-                    let score = question.calculate_score(&answer_type).into();
-                    user_state.answers.insert( question_title, (answer_type, score) );
-                    Ok(self.answer_string(&answer))
+                    if question.is_buzzer() {
+                        return Err("Could not submit answer: buzz to answer this question.".into());
+                    }
+                    let answer_type = match user_state.permutations.get(&question_id) {
+                        Some(permutation) => answer.answer().unpermuted(permutation),
+                        None => answer.answer().clone()
+                    };
+                    let time = self.opened_at.elapsed();
+                    let score = question.calculate_score(&answer_type)
+                        .map(|s| question.speed_score(s, time))
+                        .into();
+                    user_state.answer_times.insert( question_id.clone(), time.as_secs_f64() );
+                    user_state.answers.insert( question_id.clone(), (answer_type.clone(), score) );
+                    let answer_string = question.get_answer_string(&answer_type);
+                    self.share_answer(answer.user(), &question_id, answer_type, score, time.as_secs_f64());
+                    Ok(answer_string)
                 },
                 _ => Err("Could not submit answer: no question open.".into())
             }
         } else { Err("Could not submit answer: server error.".into()) }
     }
     
-    pub fn add_bonus(&mut self, username: String, bonus: i32) -> QuizResult<()> {
+    /// Shows the options of a shuffled question event in the order of the user.
+    pub fn personalize(&mut self, event: Event, username: String) -> Event {
+        let Event::Question{id,shuffled:true,..} = event else { return event };
+        let Some(len) = self.state.config.questions().get(id).and_then(|q| q.options()).map(|o| o.len()) else { return event };
+        let question_id = self.state.config.questions()[id].id().clone();
+        let Some(user_state) = self.state.users.get_mut(&username) else { return event };
+        let permutation = user_state.permutations.entry(question_id).or_insert_with(|| {
+            let mut permutation: Vec<usize> = (0..len).collect();
+            permutation.shuffle(&mut rand::rng());
+            permutation
+        });
+        match event {
+            Event::Question{title,id,question_id,total,image,question_type,round,buzzer,deadline,..} => Event::Question {
+                title, id, question_id, total, image, round, buzzer, deadline,
+                question_type: question_type.permuted(permutation),
+                shuffled: false,
+            },
+            event => event
+        }
+    }
+
+    /// The buzzer question that is open for buzzing.
+    fn buzzer_question(&self) -> Option<&Question> {
+        match self.state.status {
+            QuizStatus::Question{id,locked:false} => self.state.config.questions().get(id).filter(|q| q.is_buzzer()),
+            _ => None
+        }
+    }
+
+    /// The first user in line who has not been rejected yet.
+    fn buzzer_turn(&self) -> Option<String> {
+        let question = self.buzzer_question()?;
+        self.buzzers.iter()
+            .find(|user| self.state.users.get(*user).is_some_and(|u| !u.answers.contains_key(question.id())))
+            .cloned()
+    }
+
+    fn buzzer_event(&self, accepted: Option<String>) -> Event {
+        Event::Buzzer { turn: self.buzzer_turn(), queue: self.buzzers.clone(), accepted }
+    }
+
+    /// Records a buzz, returns an event if the user got the turn.
+    pub fn buzz(&mut self, username: String) -> QuizResult<Option<Event>> {
+        let Some(question) = self.buzzer_question() else {
+            return Err(Error::String("Could not buzz: no buzzer question open.".into()));
+        };
+        if !self.user_exists(&username) {
+            return Err(Error::String(format!("User does not exist: `{}`", username)));
+        }
+        if self.buzzers.contains(&username) || self.state.users[&username].answers.contains_key(question.id()) {
+            return Err(Error::String("Could not buzz: you already buzzed.".into()));
+        }
+        self.buzzers.push(username.clone());
+        Ok((self.buzzer_turn() == Some(username)).then(|| self.buzzer_event(None)))
+    }
+
+    /// Grades the user whose turn it is, returns the user and the event to send.
+    fn judge_buzz(&mut self, accept: bool) -> Option<(String, Event)> {
+        let question = self.buzzer_question()?.clone();
+        let user = self.buzzer_turn()?;
+        let time = self.opened_at.elapsed();
+        let score = question.speed_score(if accept { question.max_score() } else { question.min_score() }, time);
+        let time = time.as_secs_f64();
+        if let Some(user_state) = self.state.users.get_mut(&user) {
+            user_state.answer_times.insert(question.id().clone(), time);
+            user_state.answers.insert(question.id().clone(), (AnswerType::Buzz, Score::Grade(score)));
+        }
+        self.share_answer(&user, question.id(), AnswerType::Buzz, Score::Grade(score), time);
+        if accept {
+            let event = self.buzzer_event(Some(user.clone()));
+            self.lock_question();
+            Some((user, event))
+        } else {
+            Some((user, self.buzzer_event(None)))
+        }
+    }
+
+    pub fn accept_buzz(&mut self) -> Option<(String, Event)> {
+        self.judge_buzz(true)
+    }
+
+    pub fn reject_buzz(&mut self) -> Option<(String, Event)> {
+        self.judge_buzz(false)
+    }
+
+    pub fn place_wager(&mut self, username: String, wager: f64) -> QuizResult<()> {
+        let QuizStatus::Wager{id} = self.state.status else {
+            return Err(Error::String("Could not place wager: no wager question open.".into()));
+        };
+        let total = self.scores(0..self.state.config.question_count(), true).1.into_iter()
+            .find(|(user,_)| *user == username)
+            .map(|(_,score)| score);
+        let Some(total) = total else {
+            return Err(Error::String(format!("User does not exist: `{}`", username)));
+        };
+        if !(0.0..=total.max(0.0)).contains(&wager) {
+            return Err(Error::String(format!("Could not place wager: wager must be between 0 and {}.", total.max(0.0))));
+        }
+        let question_id = self.state.config.questions()[id].id().clone();
+        if let Some(user_state) = self.state.users.get_mut(&username) {
+            user_state.wagers.insert(question_id, wager);
+        }
+        Ok(())
+    }
+
+    /// Teammates of a user that share their answers, empty unless teams use shared scoring.
+    fn teammates(&self, username: &str) -> Vec<String> {
+        if !matches!(self.state.config.teams().map(|t| t.scoring()), Some(TeamScoring::Shared)) {
+            return Vec::new();
+        }
+        let Some(team) = self.state.users.get(username).and_then(|u| u.team.as_ref()) else { return Vec::new() };
+        self.state.users.iter()
+            .filter(|(user,u)| user.as_str() != username && u.team.as_ref() == Some(team))
+            .map(|(user,_)| user.clone())
+            .collect()
+    }
+
+    /// Copies an answer to the teammates of a user when teams share their answers.
+    fn share_answer(&mut self, username: &str, question_id: &str, answer: AnswerType, score: Score, time: f64) {
+        for teammate in self.teammates(username) {
+            if let Some(user_state) = self.state.users.get_mut(&teammate) {
+                user_state.answer_times.insert(question_id.to_string(), time);
+                user_state.answers.insert(question_id.to_string(), (answer.clone(), score));
+            }
+        }
+    }
+
+    pub fn add_bonus(&mut self, username: String, bonus: f64) -> QuizResult<()> {
         let Some(user_state) = self.state.users.get_mut(&username) else {
             return Err(Error::String(format!("User does not exist: `{}`", username)));
         };
-        user_state.bonus_score += bonus;
+        user_state.bonus_score = round_score(user_state.bonus_score + bonus);
         Ok(())
     }
 
@@ -319,15 +780,25 @@ impl QuizStateOwner {
 
     pub fn import_backup(&mut self, path: &PathBuf) -> QuizResult<Option<Event>> {
         let data = std::fs::read_to_string(path)?;
-        let state: QuizState = serde_json::from_str(&data)?;
+        let mut state: QuizState = serde_json::from_str(&data)?;
+        for user_state in state.users.values_mut() {
+            user_state.migrate_title_keys(&state.config);
+        }
         if self.state.users.keys().collect::<Vec<_>>() != state.users.keys().collect::<Vec<_>>() {
             return Err(Error::String("Current users and imported users do not match".into()));
         }
         self.state = state;
-        Ok(match &self.state.status {
-            QuizStatus::Question{id,..} => self.question_event(*id),
+        // a running timer belongs to the question as it was opened before the import
+        self.opened += 1;
+        self.opened_at = Instant::now();
+        self.buzzers.clear();
+        Ok(match self.state.status {
+            // reopening restarts the time limit and sends the deadline
+            QuizStatus::Question{id,locked:false} => self.reveal_question(id),
+            QuizStatus::Question{id,locked:true} => self.question_event(id),
+            QuizStatus::Wager{id} => Some(self.wager_event(id)),
             QuizStatus::Done => Some(Event::Finished),
-            QuizStatus::Lobby => Some(Event::Lobby{users:self.state.users.keys().map(|s|s.clone()).collect()})
+            QuizStatus::Lobby => Some(self.lobby_event())
         })
     }
 }
\ No newline at end of file
@@ -1,11 +1,14 @@
 
 use serde::{Serialize, Deserialize};
 use std::path::PathBuf;
+use std::time::Duration;
 
-fn stringify_answers(options: &Vec<String>, answers: &Vec<usize>) -> String {
+use super::accept::AcceptedAnswers;
+
+fn stringify_answers(options: &[String], answers: &[usize]) -> String {
 	let mut first = true;
     answers.iter()
-        .map(|i| options[*i].clone())
+        .filter_map(|i| options.get(*i).cloned())
         .fold("".to_string(),|acc,opt| {
             acc + &match first {
                 false => format!(", {}", opt),
@@ -14,27 +17,156 @@ fn stringify_answers(options: &Vec<String>, answers: &Vec<usize>) -> String {
         })
 }
 
+fn stringify_order(options: &[String], order: &[usize]) -> String {
+    order.iter()
+        .filter_map(|i| options.get(*i).cloned())
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+fn stringify_pairs(left: &[String], right: &[String], pairs: &[usize]) -> String {
+    left.iter().zip(pairs)
+        .map(|(l,r)| format!("{} - {}", l, right.get(*r).map(|s| s.as_str()).unwrap_or("?")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn check_options(problems: &mut Vec<String>, name: &str, options: &[String]) {
+    if options.is_empty() {
+        problems.push(format!("there are no {}", name));
+    }
+    let mut seen = Vec::new();
+    for option in options {
+        if seen.contains(&option) {
+            problems.push(format!("{} `{}` is listed twice", name, option));
+        }
+        seen.push(option);
+    }
+}
+
+fn check_indices(problems: &mut Vec<String>, name: &str, indices: &[usize], len: usize) {
+    for index in indices.iter().filter(|i| **i >= len) {
+        problems.push(format!("{} index {} is out of range, there are {} options", name, index, len));
+    }
+}
+
+fn stringify_number(value: f64, unit: &Option<String>) -> String {
+    match unit {
+        Some(unit) => format!("{} {}", value, unit),
+        None => value.to_string(),
+    }
+}
+
+/// Rounds a score to two decimals, so sums of fractional grades don't show float noise.
+pub(super) fn round_score(score: f64) -> f64 {
+    (score * 100.0).round() / 100.0
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct GradeRange {
-    min: usize,
-    max: usize,
+    min: f64,
+    max: f64,
 }
 
 impl GradeRange {
-    pub fn range(&self) -> std::ops::RangeInclusive<usize> {
+    pub fn new(min: f64, max: f64) -> Self {
+        GradeRange { min, max }
+    }
+
+    pub fn range(&self) -> std::ops::RangeInclusive<f64> {
         self.min..=self.max
     }
+
+    /// Scales a fraction between 0 and 1 into the grade range, rounded to two decimals.
+    pub fn scale(&self, fraction: f64) -> f64 {
+        round_score(self.min + fraction.clamp(0.0, 1.0) * (self.max - self.min))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Question {
+    /// Stable id that answers are stored under, generated from the position when not given.
+    #[serde(default)]
+    id: String,
     title: String,
     image: Option<PathBuf>,
-    grade_range: GradeRange,
+    /// Required for scored questions, polls are worth nothing and leave it out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grade_range: Option<GradeRange>,
     type_spec: QuestionType,
+    #[serde(default)]
+    accept: AcceptedAnswers,
+    /// Time limit in seconds after which the question is locked.
+    #[serde(default)]
+    time_limit: Option<u64>,
+    /// Faster answers earn more points.
+    #[serde(default)]
+    speed_scoring: bool,
+    /// Players wager part of their score before the question is revealed, a correct answer
+    /// wins the wager and any other answer loses it.
+    #[serde(default)]
+    wager: bool,
+    /// Players buzz to get the turn to answer, the host accepts or rejects their answer.
+    #[serde(default)]
+    buzzer: bool,
+    /// Category used to draw questions from a pool.
+    #[serde(default)]
+    category: Option<String>,
+    /// Difficulty used to draw questions from a pool.
+    #[serde(default)]
+    difficulty: Option<String>,
 }
 
 impl Question {
+    /// A question worth one point without any of the optional settings, as made by the importers.
+    pub fn new(title: String, type_spec: QuestionType) -> Self {
+        Question {
+            id: String::new(), title, image: None,
+            grade_range: (!matches!(type_spec, QuestionType::Poll{..})).then(|| GradeRange::new(0.0, 1.0)),
+            type_spec,
+            accept: AcceptedAnswers::default(),
+            time_limit: None, speed_scoring: false, wager: false, buzzer: false,
+            category: None, difficulty: None,
+        }
+    }
+
+    pub fn with_image(mut self, image: PathBuf) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    pub fn with_grade_range(mut self, grade_range: GradeRange) -> Self {
+        self.grade_range = Some(grade_range);
+        self
+    }
+
+    pub fn with_accept(mut self, accept: AcceptedAnswers) -> Self {
+        self.accept = accept;
+        self
+    }
+
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn with_category(mut self, category: Option<String>, difficulty: Option<String>) -> Self {
+        self.category = category;
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// Gives the question at position `index` the id `q<index+1>` if it has none.
+    pub fn generate_id(&mut self, index: usize) {
+        if self.id.is_empty() {
+            self.id = format!("q{}", index + 1);
+        }
+    }
+
     pub fn title(&self) -> &String {
         &self.title
     }
@@ -43,39 +175,184 @@ impl Question {
         self.image.as_ref()
     }
 
+    /// The grade range, 0 to 0 for polls that don't give one.
     pub fn grade_range(&self) -> GradeRange {
-        self.grade_range
+        self.grade_range.unwrap_or(GradeRange::new(0.0, 0.0))
+    }
+
+    /// Whether this is a scored question that doesn't say what it is worth.
+    pub fn is_missing_grade_range(&self) -> bool {
+        self.grade_range.is_none() && self.is_scored()
     }
 
     pub fn type_spec(&self) -> &QuestionType {
         &self.type_spec
     }
+
+    pub fn accept(&self) -> &AcceptedAnswers {
+        &self.accept
+    }
+
+    pub fn is_buzzer(&self) -> bool {
+        self.buzzer
+    }
+
+    pub fn category(&self) -> Option<&String> {
+        self.category.as_ref()
+    }
+
+    pub fn difficulty(&self) -> Option<&String> {
+        self.difficulty.as_ref()
+    }
+
+    pub fn is_wager(&self) -> bool {
+        self.wager
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.map(Duration::from_secs)
+    }
 }
 
 impl Question {
-    pub fn max_score(&self) -> usize {
-        self.grade_range.max
+    pub fn max_score(&self) -> f64 {
+        self.grade_range().max
+    }
+
+    pub fn min_score(&self) -> f64 {
+        self.grade_range().min
+    }
+
+    /// Whether answers to this question count towards the ranking.
+    pub fn is_scored(&self) -> bool {
+        !matches!(self.type_spec, QuestionType::Poll{..})
+    }
+
+    /// The options a player can choose from, if this is a choice question.
+    pub fn options(&self) -> Option<&Vec<String>> {
+        match &self.type_spec {
+            QuestionType::MultiChoice{options,..}
+            | QuestionType::MultiOption{options,..}
+            | QuestionType::Poll{options} => Some(options),
+            _ => None
+        }
+    }
+
+    /// Whether the options of this question can be shuffled per user.
+    pub fn has_shuffleable_options(&self) -> bool {
+        matches!(self.type_spec, QuestionType::MultiChoice{..} | QuestionType::MultiOption{..})
+    }
+
+    /// The options chosen in an answer to a choice question.
+    pub fn chosen_options(&self, answer: &AnswerType) -> Vec<usize> {
+        match answer {
+            AnswerType::MultiChoice(answer) | AnswerType::Poll(answer) => vec![*answer],
+            AnswerType::MultiOption(answers) => answers.clone(),
+            _ => Vec::new()
+        }
     }
 
-    pub fn calculate_score(&self, answer: &AnswerType) -> Option<usize> {
+    pub fn calculate_score(&self, answer: &AnswerType) -> Option<f64> {
         match (&self.type_spec, answer) {
             (QuestionType::MultiChoice{answer:correct_answer,..},AnswerType::MultiChoice(answer))
-                => if answer == correct_answer { Some(self.max_score()) } else { Some(0) },
-            (QuestionType::MultiOption{answers:correct_answers,..},AnswerType::MultiOption(answers))
-                => if answers == correct_answers { Some(self.max_score()) } else { Some(0) },
-            (QuestionType::Open,AnswerType::Open(_)) => None,
+                => if answer == correct_answer { Some(self.max_score()) } else { Some(self.min_score()) },
+            (QuestionType::MultiOption{answers:correct_answers,scoring,..},AnswerType::MultiOption(answers))
+                => Some(scoring.score(self.grade_range(), correct_answers, answers)),
+            (QuestionType::Ordering{answer:correct_order,scoring,..},AnswerType::Ordering(order))
+                => Some(self.grade_range().scale(scoring.fraction(correct_order, order))),
+            (QuestionType::Matching{answer:correct_pairs,..},AnswerType::Matching(pairs)) => {
+                let correct = correct_pairs.iter().zip(pairs).filter(|(a,b)| a == b).count();
+                Some(self.grade_range().scale(correct as f64 / correct_pairs.len().max(1) as f64))
+            },
+            (QuestionType::Numeric{value,tolerance,..},AnswerType::Numeric(answer))
+                => tolerance.contains(*value, *answer).map(|c| if c { self.max_score() } else { self.min_score() }),
+            (QuestionType::Open,AnswerType::Open(answer))
+                => if self.accept.matches(answer) { Some(self.max_score()) } else { None },
             _ => None
         }
     }
 
+    /// Lowers the part of a score above the minimum the longer an answer took, down to half of it
+    /// when it took the full time limit (or 30 seconds without one).
+    pub fn speed_score(&self, score: f64, time: Duration) -> f64 {
+        if !self.speed_scoring {
+            return score;
+        }
+        let limit = self.time_limit().unwrap_or(Duration::from_secs(30));
+        let factor = 1.0 - 0.5 * (time.as_secs_f64() / limit.as_secs_f64()).clamp(0.0, 1.0);
+        round_score(self.min_score() + (score - self.min_score()) * factor)
+    }
+
+    /// Whether an ungraded open answer is close to one of the accepted answers.
+    pub fn is_near_miss(&self, answer: &str) -> bool {
+        matches!(self.type_spec, QuestionType::Open) && self.accept.is_near_miss(answer)
+    }
+
+    /// Whether this question can only be scored once all answers are in, i.e. when it is locked.
+    pub fn scored_on_lock(&self) -> bool {
+        matches!(self.type_spec, QuestionType::Numeric{tolerance:Tolerance::Closest,..})
+    }
+
+    /// Scores all submitted answers of a question together.
+    /// Questions that are not scored on lock fall back to `calculate_score` per answer.
+    pub fn calculate_scores(&self, answers: &[&AnswerType]) -> Vec<Option<f64>> {
+        match &self.type_spec {
+            QuestionType::Numeric{value,tolerance:Tolerance::Closest,..} => {
+                let distance = |answer: &AnswerType| match answer {
+                    AnswerType::Numeric(answer) => Some((answer - value).abs()),
+                    _ => None
+                };
+                let closest = answers.iter()
+                    .filter_map(|a| distance(a))
+                    .fold(f64::INFINITY, f64::min);
+                answers.iter().map(|a| {
+                    distance(a).map(|d| if d == closest { self.max_score() } else { self.min_score() })
+                }).collect()
+            },
+            _ => answers.iter().map(|a| self.calculate_score(a)).collect()
+        }
+    }
+
+    /// Problems that would break the question during a quiz.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.title.trim().is_empty() {
+            problems.push("title is empty".to_string());
+        }
+        let range = self.grade_range();
+        if self.is_missing_grade_range() {
+            problems.push("grade range is missing".to_string());
+        } else if range.min > range.max {
+            problems.push(format!("grade range minimum {} is above maximum {}", range.min, range.max));
+        } else if self.is_scored() && range.min == range.max {
+            problems.push(format!("grade range {}..{} is empty, the question is worth no points", range.min, range.max));
+        }
+        if self.time_limit == Some(0) {
+            problems.push("time limit is 0 seconds".to_string());
+        }
+        problems.extend(self.accept.validate());
+        problems.extend(self.type_spec.validate());
+        problems
+    }
+
     pub fn get_answer_string(&self, answer: &AnswerType) -> String {
         match (&self.type_spec, answer) {
             (QuestionType::MultiChoice{options,..},AnswerType::MultiChoice(answer))
-                => options[*answer].clone(),
+                => options.get(*answer).cloned().unwrap_or_default(),
             (QuestionType::MultiOption{options,..},AnswerType::MultiOption(answers))
                 => stringify_answers(options,answers),
+            (QuestionType::Ordering{options,..},AnswerType::Ordering(order))
+                => stringify_order(options,order),
+            (QuestionType::Matching{left,right,..},AnswerType::Matching(pairs))
+                => stringify_pairs(left,right,pairs),
+            (QuestionType::Numeric{unit,..},AnswerType::Numeric(answer))
+                => stringify_number(*answer, unit),
+            (QuestionType::Poll{options},AnswerType::Poll(answer))
+                => options.get(*answer).cloned().unwrap_or_default(),
             (QuestionType::Open,AnswerType::Open(answer))
                 => answer.clone(),
+            (_,AnswerType::Buzz)
+                => "buzzed".to_string(),
             _ => "".to_string()
         }
     }
@@ -90,18 +367,220 @@ pub enum QuestionType {
     MultiOption {
         options: Vec<String>,
         answers: Vec<usize>,
+        #[serde(default)]
+        scoring: OptionScoring,
+    },
+    Ordering {
+        options: Vec<String>,
+        answer: Vec<usize>,
+        #[serde(default)]
+        scoring: OrderScoring,
+    },
+    /// Every item on the left is matched to the item on the right at the same index in `answer`.
+    Matching {
+        left: Vec<String>,
+        right: Vec<String>,
+        answer: Vec<usize>,
+    },
+    /// A question without a correct answer that is never scored.
+    Poll {
+        options: Vec<String>,
+    },
+    Numeric {
+        value: f64,
+        #[serde(default)]
+        unit: Option<String>,
+        #[serde(default)]
+        tolerance: Tolerance,
     },
     Open
 }
 
-impl std::fmt::Display for QuestionType {
+impl QuestionType {
+    /// The correct answer in readable form, polls and open questions have none.
+    pub fn expected_answer(&self) -> Option<String> {
+        match self {
+            QuestionType::MultiChoice{options,answer} => Some(options.get(*answer).map(|s| s.as_str()).unwrap_or("?").to_string()),
+            QuestionType::MultiOption{options,answers,..} => Some(stringify_answers(options,answers)),
+            QuestionType::Ordering{options,answer,..} => Some(stringify_order(options,answer)),
+            QuestionType::Matching{left,right,answer} => Some(stringify_pairs(left,right,answer)),
+            QuestionType::Numeric{value,unit,tolerance} => Some(format!("{}{}", stringify_number(*value, unit), tolerance)),
+            QuestionType::Poll{..} | QuestionType::Open => None,
+        }
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match self {
+            QuestionType::MultiChoice{options,answer} => {
+                check_options(&mut problems, "options", options);
+                check_indices(&mut problems, "answer", &[*answer], options.len());
+            },
+            QuestionType::MultiOption{options,answers,..} => {
+                check_options(&mut problems, "options", options);
+                check_indices(&mut problems, "answer", answers, options.len());
+                if (1..answers.len()).any(|i| answers[..i].contains(&answers[i])) {
+                    problems.push("an answer is listed twice".to_string());
+                }
+            },
+            QuestionType::Ordering{options,answer,..} => {
+                check_options(&mut problems, "options", options);
+                let mut sorted = answer.clone();
+                sorted.sort();
+                if sorted != (0..options.len()).collect::<Vec<_>>() {
+                    problems.push("answer must list every option index exactly once".to_string());
+                }
+            },
+            QuestionType::Matching{left,right,answer} => {
+                check_options(&mut problems, "left items", left);
+                check_options(&mut problems, "right items", right);
+                if answer.len() != left.len() {
+                    problems.push(format!("answer has {} matches for {} left items", answer.len(), left.len()));
+                }
+                check_indices(&mut problems, "answer", answer, right.len());
+            },
+            QuestionType::Poll{options} => check_options(&mut problems, "options", options),
+            QuestionType::Numeric{tolerance: Tolerance::Absolute(t) | Tolerance::Relative(t),..} if *t < 0.0 => {
+                problems.push("tolerance is negative".to_string());
+            },
+            QuestionType::Numeric{..} | QuestionType::Open => {},
+        }
+        problems
+    }
+}
+
+/// How a multi-option answer is scored.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum OptionScoring {
+    /// Full points only when exactly the correct options are ticked.
+    #[default]
+    AllOrNothing,
+    /// Credit for every correct option ticked, minus `penalty` times that credit for every wrong tick.
+    /// The score never drops below the minimum of the grade range.
+    PerOption {
+        #[serde(default)]
+        penalty: f64,
+    },
+}
+
+impl OptionScoring {
+    pub fn score(&self, grade_range: GradeRange, correct_answers: &[usize], answers: &[usize]) -> f64 {
+        let mut ticked = answers.to_vec();
+        ticked.sort();
+        ticked.dedup();
+        let correct = ticked.iter().filter(|a| correct_answers.contains(a)).count();
+        let wrong = ticked.len() - correct;
+        match self {
+            OptionScoring::AllOrNothing => {
+                let all = wrong == 0 && correct == correct_answers.len();
+                if all { grade_range.max } else { grade_range.min }
+            },
+            OptionScoring::PerOption { penalty } => {
+                let credit = correct as f64 - penalty * wrong as f64;
+                grade_range.scale(credit / correct_answers.len().max(1) as f64)
+            },
+        }
+    }
+}
+
+/// How partial credit is given for an ordering answer.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum OrderScoring {
+    /// Credit for every item placed at its correct position.
+    #[default]
+    Position,
+    /// Credit for every pair of items in the correct relative order.
+    Inversions,
+}
+
+impl OrderScoring {
+    /// Fraction of the credit an order earns, 0 if either order is not a permutation of the items.
+    pub fn fraction(&self, correct_order: &[usize], order: &[usize]) -> f64 {
+        let is_permutation = |order: &[usize]| {
+            let mut sorted = order.to_vec();
+            sorted.sort();
+            sorted == (0..correct_order.len()).collect::<Vec<_>>()
+        };
+        if correct_order.is_empty() || !is_permutation(correct_order) || !is_permutation(order) {
+            return 0.0;
+        }
+        match self {
+            OrderScoring::Position => {
+                let correct = correct_order.iter().zip(order).filter(|(a,b)| a == b).count();
+                correct as f64 / correct_order.len() as f64
+            },
+            OrderScoring::Inversions => {
+                if correct_order.len() < 2 {
+                    return 1.0;
+                }
+                let position = |item: &usize| order.iter().position(|i| i == item).unwrap();
+                let positions: Vec<_> = correct_order.iter().map(position).collect();
+                let pairs = positions.len() * (positions.len() - 1) / 2;
+                let inversions = positions.iter().enumerate()
+                    .map(|(i,a)| positions[i+1..].iter().filter(|b| a > b).count())
+                    .sum::<usize>();
+                1.0 - inversions as f64 / pairs as f64
+            },
+        }
+    }
+}
+
+/// How far a numeric answer may be off from the correct value.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum Tolerance {
+    /// The answer must be exactly right.
+    #[default]
+    Exact,
+    /// Maximum absolute difference from the correct value.
+    Absolute(f64),
+    /// Maximum difference as a fraction of the correct value, e.g. 0.1 for 10%.
+    Relative(f64),
+    /// Only the answers closest to the correct value get the points.
+    Closest,
+}
+
+impl Tolerance {
+    /// Whether an answer lies within the tolerance band, `None` if that can't be decided per answer.
+    pub fn contains(&self, value: f64, answer: f64) -> Option<bool> {
+        let difference = (answer - value).abs();
+        match self {
+            Tolerance::Exact => Some(difference == 0.0),
+            Tolerance::Absolute(t) => Some(difference <= *t),
+            Tolerance::Relative(t) => Some(difference <= (value * t).abs()),
+            Tolerance::Closest => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Tolerance {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
-            QuestionType::MultiChoice { options, answer } 
-				=> f.write_fmt(format_args!("MultiChoice\nexpected answer: {}", options[*answer])),
-            QuestionType::MultiOption { options, answers } 
-				=> f.write_fmt(format_args!("MultiOption\nexpected answer: {}", stringify_answers(options,answers))),
-            QuestionType::Open => f.write_str("Open"),
+            Tolerance::Exact => Ok(()),
+            Tolerance::Absolute(t) => f.write_fmt(format_args!(" (±{})", t)),
+            // rounded to hide float noise, 0.07 * 100.0 is 7.000000000000001
+            Tolerance::Relative(t) => f.write_fmt(format_args!(" (±{}%)", (t * 100.0 * 1e6).round() / 1e6)),
+            Tolerance::Closest => f.write_str(" (closest wins)"),
+        }
+    }
+}
+
+impl std::fmt::Display for QuestionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            QuestionType::MultiChoice{..} => "MultiChoice",
+            QuestionType::MultiOption{..} => "MultiOption",
+            QuestionType::Ordering{..} => "Ordering",
+            QuestionType::Matching{..} => "Matching",
+            QuestionType::Poll{..} => "Poll",
+            QuestionType::Numeric{..} => "Numeric",
+            QuestionType::Open => "Open",
+        };
+        match (self, self.expected_answer()) {
+            (QuestionType::Poll { options }, _)
+				=> f.write_fmt(format_args!("{}\noptions: {}", name, options.join(", "))),
+            (_, Some(expected))
+				=> f.write_fmt(format_args!("{}\nexpected answer: {}", name, expected)),
+            (_, None) => f.write_str(name),
         }
     }
 }
@@ -110,14 +589,40 @@ impl std::fmt::Display for QuestionType {
 pub enum QuestionSendType {
     MultiChoice(Vec<String>),
     MultiOption(Vec<String>),
+    Ordering(Vec<String>),
+    Matching {
+        left: Vec<String>,
+        right: Vec<String>,
+    },
+    Poll(Vec<String>),
+    Numeric(Option<String>),
     Open
 }
 
+fn permute(options: &[String], permutation: &[usize]) -> Vec<String> {
+    permutation.iter().filter_map(|i| options.get(*i).cloned()).collect()
+}
+
+impl QuestionSendType {
+    /// Orders the options as shown to a user, `permutation[i]` is the option shown at position `i`.
+    pub fn permuted(self, permutation: &[usize]) -> Self {
+        match self {
+            QuestionSendType::MultiChoice(options) => QuestionSendType::MultiChoice(permute(&options, permutation)),
+            QuestionSendType::MultiOption(options) => QuestionSendType::MultiOption(permute(&options, permutation)),
+            other => other
+        }
+    }
+}
+
 impl From<&QuestionType> for QuestionSendType {
     fn from(q: &QuestionType) -> Self {
         match q {
             QuestionType::MultiChoice{options,..} => QuestionSendType::MultiChoice(options.clone()),
             QuestionType::MultiOption{options,..} => QuestionSendType::MultiOption(options.clone()),
+            QuestionType::Ordering{options,..} => QuestionSendType::Ordering(options.clone()),
+            QuestionType::Matching{left,right,..} => QuestionSendType::Matching{left:left.clone(),right:right.clone()},
+            QuestionType::Poll{options} => QuestionSendType::Poll(options.clone()),
+            QuestionType::Numeric{unit,..} => QuestionSendType::Numeric(unit.clone()),
             QuestionType::Open => QuestionSendType::Open
         }
     }
@@ -126,7 +631,7 @@ impl From<&QuestionType> for QuestionSendType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Answer {
     user: String,
-    question: String,
+    question_id: String,
     answer: AnswerType,
 }
 
@@ -135,8 +640,8 @@ impl Answer {
         &self.user
     }
 
-    pub fn question(&self) -> &String {
-        &self.question
+    pub fn question_id(&self) -> &String {
+        &self.question_id
     }
 
     pub fn answer(&self) -> &AnswerType {
@@ -144,9 +649,131 @@ impl Answer {
     }
 }
 
+impl AnswerType {
+    /// Maps the options chosen by a user with shuffled options back to the options of the question.
+    pub fn unpermuted(&self, permutation: &[usize]) -> Self {
+        let original = |i: &usize| permutation.get(*i).copied().unwrap_or(*i);
+        match self {
+            AnswerType::MultiChoice(answer) => AnswerType::MultiChoice(original(answer)),
+            AnswerType::MultiOption(answers) => AnswerType::MultiOption(answers.iter().map(original).collect()),
+            other => other.clone()
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum AnswerType {
     MultiChoice(usize),
     MultiOption(Vec<usize>),
-    Open(String)
-}
\ No newline at end of file
+    Ordering(Vec<usize>),
+    Matching(Vec<usize>),
+    Poll(usize),
+    Numeric(f64),
+    Open(String),
+    /// Answered out loud after buzzing, graded by the host.
+    Buzz,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_or_nothing_needs_exactly_the_correct_options() {
+        let range = GradeRange::new(-1.0, 2.0);
+        let scoring = OptionScoring::AllOrNothing;
+        assert_eq!(scoring.score(range, &[0, 2], &[2, 0]), 2.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[0]), -1.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[0, 1, 2]), -1.0);
+    }
+
+    #[test]
+    fn per_option_penalties_are_floored_at_the_minimum() {
+        let range = GradeRange::new(0.0, 2.0);
+        let scoring = OptionScoring::PerOption { penalty: 1.0 };
+        assert_eq!(scoring.score(range, &[0, 2], &[0]), 1.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[0, 0]), 1.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[0, 1]), 0.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[1, 3]), 0.0);
+
+        let range = GradeRange::new(-1.0, 3.0);
+        assert_eq!(scoring.score(range, &[0, 2], &[1, 3]), -1.0);
+        // (2 correct - 0.5 * 1 wrong) / 2 = 0.75 of the range above -1
+        assert_eq!(OptionScoring::PerOption { penalty: 0.5 }.score(range, &[0, 2], &[0, 1, 2]), 2.0);
+    }
+
+    #[test]
+    fn ordering_by_position_and_by_inversions() {
+        assert_eq!(OrderScoring::Position.fraction(&[2, 0, 1], &[2, 0, 1]), 1.0);
+        assert_eq!(OrderScoring::Position.fraction(&[2, 0, 1], &[2, 1, 0]), 1.0 / 3.0);
+        // one swapped neighbour pair out of six pairs
+        assert_eq!(OrderScoring::Inversions.fraction(&[0, 1, 2, 3], &[1, 0, 2, 3]), 5.0 / 6.0);
+        assert_eq!(OrderScoring::Inversions.fraction(&[0, 1, 2, 3], &[3, 2, 1, 0]), 0.0);
+        assert_eq!(OrderScoring::Inversions.fraction(&[2, 0, 1], &[2, 0, 1]), 1.0);
+    }
+
+    #[test]
+    fn an_order_that_is_not_a_permutation_earns_nothing() {
+        for scoring in [OrderScoring::Position, OrderScoring::Inversions] {
+            assert_eq!(scoring.fraction(&[0, 1, 2], &[0, 0, 1]), 0.0);
+            assert_eq!(scoring.fraction(&[0, 1, 2], &[0, 1]), 0.0);
+            assert_eq!(scoring.fraction(&[0, 1, 2], &[0, 1, 5]), 0.0);
+            // a broken config answer must not panic
+            assert_eq!(scoring.fraction(&[0, 1, 5], &[0, 1, 2]), 0.0);
+        }
+    }
+
+    #[test]
+    fn scored_questions_need_a_non_empty_grade_range() {
+        let open = Question { grade_range: None, ..Question::new("Open".into(), QuestionType::Open) };
+        assert!(open.is_missing_grade_range());
+        assert_eq!(open.validate(), ["grade range is missing"]);
+        let empty = Question::new("Open".into(), QuestionType::Open).with_grade_range(GradeRange::new(1.0, 1.0));
+        assert_eq!(empty.validate().len(), 1);
+        let poll = Question::new("Poll".into(), QuestionType::Poll { options: vec!["a".into(), "b".into()] });
+        assert!(!poll.is_missing_grade_range());
+        assert!(poll.validate().is_empty());
+        assert_eq!(poll.max_score(), 0.0);
+    }
+
+    #[test]
+    fn numeric_tolerance_bands() {
+        assert_eq!(Tolerance::Exact.contains(10.0, 10.0), Some(true));
+        assert_eq!(Tolerance::Exact.contains(10.0, 10.1), Some(false));
+        assert_eq!(Tolerance::Absolute(0.5).contains(10.0, 10.5), Some(true));
+        assert_eq!(Tolerance::Absolute(0.5).contains(10.0, 9.4), Some(false));
+        assert_eq!(Tolerance::Relative(0.1).contains(-50.0, -45.0), Some(true));
+        assert_eq!(Tolerance::Relative(0.1).contains(-50.0, -44.0), Some(false));
+        assert_eq!(Tolerance::Closest.contains(10.0, 10.0), None);
+        assert_eq!(Tolerance::Relative(0.07).to_string(), " (±7%)");
+    }
+
+    #[test]
+    fn closest_answers_win_including_ties() {
+        let question = Question::new("How many?".into(), QuestionType::Numeric { value: 100.0, unit: None, tolerance: Tolerance::Closest })
+            .with_grade_range(GradeRange::new(-1.0, 2.0));
+        assert!(question.scored_on_lock());
+        let answers = [AnswerType::Numeric(90.0), AnswerType::Numeric(110.0), AnswerType::Numeric(120.0), AnswerType::Open("100".into())];
+        let scores = question.calculate_scores(&answers.iter().collect::<Vec<_>>());
+        assert_eq!(scores, [Some(2.0), Some(2.0), Some(-1.0), None]);
+    }
+
+    #[test]
+    fn speed_scoring_halves_the_score_above_the_minimum_at_the_limit() {
+        let question = Question {
+            time_limit: Some(20),
+            speed_scoring: true,
+            ..Question::new("Quick!".into(), QuestionType::Open).with_grade_range(GradeRange::new(-1.0, 2.0))
+        };
+        assert_eq!(question.speed_score(2.0, Duration::ZERO), 2.0);
+        assert_eq!(question.speed_score(2.0, Duration::from_secs(10)), 1.25);
+        assert_eq!(question.speed_score(2.0, Duration::from_secs(20)), 0.5);
+        assert_eq!(question.speed_score(2.0, Duration::from_secs(60)), 0.5);
+        assert_eq!(question.speed_score(-1.0, Duration::from_secs(10)), -1.0);
+        // 30 seconds without a time limit, rounded to two decimals
+        let question = Question { time_limit: None, ..question };
+        assert_eq!(question.speed_score(2.0, Duration::from_secs(7)), 1.65);
+        let question = Question { speed_scoring: false, ..question };
+        assert_eq!(question.speed_score(2.0, Duration::from_secs(7)), 2.0);
+    }
+}
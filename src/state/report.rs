@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use base64::Engine;
+use html_escape::{encode_double_quoted_attribute as attr, encode_text as text};
+
+use super::{Results, Score};
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 60em; margin: 2em auto; color: #222; }
+table { border-collapse: collapse; margin: 0.5em 0 1em; }
+th, td { border: 1px solid #bbb; padding: 0.2em 0.6em; text-align: left; }
+td.num { text-align: right; }
+section { break-inside: avoid; margin-bottom: 2em; }
+img { max-width: 100%; max-height: 20em; }
+.bar { display: inline-block; height: 0.8em; background: #4a7ebb; }
+";
+
+/// Everything the report shows about one question.
+pub(super) struct QuestionReport {
+    pub title: String,
+    pub max_score: f64,
+    pub expected: Option<String>,
+    pub accepted: Vec<String>,
+    /// The image as a data url, so the report does not depend on the quiz root.
+    pub image: Option<String>,
+    pub distribution: Option<Vec<(String, usize)>>,
+    /// Users with their answer and grade, only for open questions.
+    pub answers: Vec<(String, String, Score)>,
+}
+
+/// Reads an image from the quiz root into a data url, `None` if it cannot be read.
+pub(super) fn inline_image(root: &Path, image: &Path) -> Option<String> {
+    let path = root.join(image.strip_prefix("/").unwrap_or(image));
+    let mime = match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => return None,
+    };
+    let data = std::fs::read(path).ok()?;
+    Some(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+fn grade(score: &Score, max_score: f64) -> String {
+    match score {
+        Score::Grade(g) => format!("{}/{}", g, max_score),
+        Score::Ungraded => "ungraded".into(),
+    }
+}
+
+/// Renders a self-contained html page with the ranking and a summary of every question.
+pub(super) fn render(results: &Results, teams: &[(String, f64)], questions: &[QuestionReport]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n", text(&results.title), STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", text(&results.title)));
+
+    let with_teams = results.users.iter().any(|u| u.team.is_some());
+    html.push_str("<section>\n<h2>Ranking</h2>\n<table>\n<tr><th>Rank</th><th>User</th>");
+    if with_teams {
+        html.push_str("<th>Team</th>");
+    }
+    html.push_str("<th>Bonus</th><th>Total</th></tr>\n");
+    for user in &results.users {
+        html.push_str(&format!("<tr><td class=\"num\">{}</td><td>{}</td>", user.rank, text(&user.user)));
+        if with_teams {
+            html.push_str(&format!("<td>{}</td>", text(user.team.as_deref().unwrap_or_default())));
+        }
+        html.push_str(&format!("<td class=\"num\">{}</td><td class=\"num\">{}/{}</td></tr>\n", user.bonus, user.total, results.max_score));
+    }
+    html.push_str("</table>\n");
+    if !teams.is_empty() {
+        html.push_str("<h3>Teams</h3>\n<table>\n<tr><th>Team</th><th>Score</th></tr>\n");
+        for (team, score) in teams {
+            html.push_str(&format!("<tr><td>{}</td><td class=\"num\">{}</td></tr>\n", text(team), score));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</section>\n");
+
+    for (i, question) in questions.iter().enumerate() {
+        html.push_str(&format!("<section>\n<h2>{}. {}</h2>\n", i + 1, text(&question.title)));
+        if let Some(image) = &question.image {
+            html.push_str(&format!("<img src=\"{}\" alt=\"\">\n", attr(image)));
+        }
+        if let Some(expected) = &question.expected {
+            html.push_str(&format!("<p>Correct answer: <b>{}</b></p>\n", text(expected)));
+        }
+        if !question.accepted.is_empty() {
+            html.push_str(&format!("<p>Accepted answers: {}</p>\n", text(&question.accepted.join(", "))));
+        }
+        if let Some(distribution) = &question.distribution {
+            let most = distribution.iter().map(|(_, count)| *count).max().unwrap_or_default().max(1) as f64;
+            html.push_str("<table>\n<tr><th>Option</th><th>Answers</th><th></th></tr>\n");
+            for (option, count) in distribution {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td><span class=\"bar\" style=\"width: {:.1}em\"></span></td></tr>\n",
+                    text(option), count, *count as f64 * 10.0 / most));
+            }
+            html.push_str("</table>\n");
+        }
+        if !question.answers.is_empty() {
+            html.push_str("<table>\n<tr><th>User</th><th>Answer</th><th>Grade</th></tr>\n");
+            for (user, answer, score) in &question.answers {
+                html.push_str(&format!("<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>\n",
+                    text(user), text(answer), grade(score, question.max_score)));
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
@@ -2,35 +2,51 @@
 
 use tokio::sync::mpsc::Sender;
 use tokio::sync::oneshot::{self, Sender as Return};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use crate::error::QuizResult;
 
-use super::{Answer, Event, Question, QuestionType, QuizStatus, Ranking, Score};
+use super::{Answer, Distribution, Event, Question, QuestionType, QuizStatus, Ranking, Results, Score};
+
+pub type QuestionAnswers = (HashMap<String,(String,Score,Option<f64>)>,std::ops::RangeInclusive<f64>);
+
+pub type TeamMembers = Vec<(String, Vec<String>)>;
 
 pub enum QuizStateJob {
     RootPath(Return<PathBuf>),
     Title(Return<String>),
     Status(Return<QuizStatus>),
     UserCount(Return<usize>),
-    Users(Return<Vec<(String, i32)>>),
+    Users(Return<Vec<(String, f64)>>),
     UserExists(String, Return<bool>),
+    JoinTeam(String, String, Return<QuizResult<()>>),
+    Teams(Return<Option<TeamMembers>>),
     Lobby(Return<Option<Event>>),
     AddUser(String, Return<QuizResult<()>>),
     RemoveUser(String, Return<QuizResult<()>>),
     Questions(Return<Vec<(String, QuestionType)>>),
     Question(usize, Return<Option<Question>>),
+    RoundOf(usize, Return<Option<String>>),
     Ranking(Return<Ranking>),
+    Results(Return<Results>),
+    Report(Return<String>),
     UsersNoAnswer(Return<Vec<String>>),
     UngradedAnswers(Return<Vec<usize>>),
-    Answers(usize, Return<Option<(HashMap<String,(String,Score)>,std::ops::RangeInclusive<usize>)>>),
-    UpdateGrade(String, String, usize),
+    Answers(usize, Return<Option<QuestionAnswers>>),
+    Distribution(usize, Return<Option<Distribution>>),
+    UpdateGrade(String, String, f64),
     Start(Return<Option<Event>>),
     Next(Return<Option<Event>>),
     LockQuestion,
+    TimeUp(usize, usize),
     Redo(usize, Return<Option<Event>>),
     SubmitAnswer(Answer, Return<Result<String,String>>),
-    Bonus(String, i32, Return<QuizResult<()>>),
+    Personalize(Event, String, Return<Event>),
+    Wager(String, f64, Return<QuizResult<()>>),
+    Buzz(String, Return<QuizResult<Option<Event>>>),
+    AcceptBuzz(Return<Option<(String, Event)>>),
+    RejectBuzz(Return<Option<(String, Event)>>),
+    Bonus(String, f64, Return<QuizResult<()>>),
     Backup(PathBuf, Return<QuizResult<()>>),
     ImportBackup(PathBuf, Return<QuizResult<Option<Event>>>),
 }
@@ -69,21 +85,33 @@ impl QuizStateService {
         recv.await.expect("Receive failed")
     }
 
-    pub async fn users(&self) -> Vec<(String, i32)> {
+    pub async fn users(&self) -> Vec<(String, f64)> {
         let (send, recv) = oneshot::channel();
         self.job_channel.send(QuizStateJob::Users(send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
-    pub async fn user_exists(&self, username: &String) -> bool {
+    pub async fn user_exists(&self, username: &str) -> bool {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::UserExists(username.clone(), send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::UserExists(username.to_string(), send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
     
-    pub async fn remove_user(&self, username: &String) -> QuizResult<()> {
+    pub async fn remove_user(&self, username: &str) -> QuizResult<()> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::RemoveUser(username.to_string(), send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn join_team(&self, username: &str, team: &str) -> QuizResult<()> {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::RemoveUser(username.clone(), send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::JoinTeam(username.to_string(), team.to_string(), send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn teams(&self) -> Option<TeamMembers> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Teams(send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
@@ -93,9 +121,9 @@ impl QuizStateService {
         recv.await.expect("Receive failed")
     }
 
-    pub async fn add_user(&self, username: &String) -> QuizResult<()> {
+    pub async fn add_user(&self, username: &str) -> QuizResult<()> {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::AddUser(username.clone(), send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::AddUser(username.to_string(), send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
@@ -111,12 +139,30 @@ impl QuizStateService {
         recv.await.expect("Receive failed")
     }
 
+    pub async fn round_of(&self, index: usize) -> Option<String> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::RoundOf(index, send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
     pub async fn ranking(&self) -> Ranking {
         let (send, recv) = oneshot::channel();
         self.job_channel.send(QuizStateJob::Ranking(send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
+    pub async fn results(&self) -> Results {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Results(send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn report(&self) -> String {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Report(send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
     pub async fn no_answer_users(&self) -> Vec<String> {
         let (send, recv) = oneshot::channel();
         self.job_channel.send(QuizStateJob::UsersNoAnswer(send)).await.expect("Send failed");
@@ -129,16 +175,20 @@ impl QuizStateService {
         recv.await.expect("Receive failed")
     }
 
-    pub async fn answers(&self, index: usize)
-        -> Option<(HashMap<String,(String,Score)>,std::ops::RangeInclusive<usize>)>
-    {
+    pub async fn answers(&self, index: usize) -> Option<QuestionAnswers> {
         let (send, recv) = oneshot::channel();
         self.job_channel.send(QuizStateJob::Answers(index, send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
-    pub async fn update_grade(&self, user: &String, question_title: &String, grade: usize) {
-        let job = QuizStateJob::UpdateGrade(user.clone(), question_title.clone(), grade);
+    pub async fn distribution(&self, index: usize) -> Option<Distribution> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Distribution(index, send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn update_grade(&self, user: &str, question_id: &str, grade: f64) {
+        let job = QuizStateJob::UpdateGrade(user.to_string(), question_id.to_string(), grade);
         self.job_channel.send(job).await.expect("Send failed");
     }
 
@@ -170,21 +220,51 @@ impl QuizStateService {
         recv.await.expect("Receive failed")
     }
     
-    pub async fn add_bonus(&self, user: &String, bonus: i32) -> QuizResult<()> {
+    pub async fn personalize(&self, event: Event, user: &str) -> Event {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Personalize(event, user.to_string(), send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn buzz(&self, user: &str) -> QuizResult<Option<Event>> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Buzz(user.to_string(), send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn accept_buzz(&self) -> Option<(String, Event)> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::AcceptBuzz(send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn reject_buzz(&self) -> Option<(String, Event)> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::RejectBuzz(send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn place_wager(&self, user: &str, wager: f64) -> QuizResult<()> {
+        let (send, recv) = oneshot::channel();
+        self.job_channel.send(QuizStateJob::Wager(user.to_string(), wager, send)).await.expect("Send failed");
+        recv.await.expect("Receive failed")
+    }
+
+    pub async fn add_bonus(&self, user: &str, bonus: f64) -> QuizResult<()> {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::Bonus(user.clone(), bonus, send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::Bonus(user.to_string(), bonus, send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
-    pub async fn backup(&self, path: &PathBuf) -> QuizResult<()> {
+    pub async fn backup(&self, path: &Path) -> QuizResult<()> {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::Backup(path.clone(), send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::Backup(path.to_path_buf(), send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 
-    pub async fn import_backup(&self, path: &PathBuf) -> QuizResult<Option<Event>> {
+    pub async fn import_backup(&self, path: &Path) -> QuizResult<Option<Event>> {
         let (send, recv) = oneshot::channel();
-        self.job_channel.send(QuizStateJob::ImportBackup(path.clone(), send)).await.expect("Send failed");
+        self.job_channel.send(QuizStateJob::ImportBackup(path.to_path_buf(), send)).await.expect("Send failed");
         recv.await.expect("Receive failed")
     }
 }
\ No newline at end of file
@@ -6,43 +6,75 @@ use super::question::QuestionSendType;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
     Lobby{
-        users: Vec<String>
+        users: Vec<String>,
+        /// Teams and their members, if players play in teams.
+        teams: Option<Vec<(String, Vec<String>)>>,
     },
     Question {
         title: String,
         id: usize,
+        /// Id of the question, to submit answers to.
+        question_id: String,
         total: usize,
         image: Option<String>,
         question_type: QuestionSendType,
+        round: Option<RoundInfo>,
+        /// Players buzz to get the turn to answer.
+        buzzer: bool,
+        /// The options are shuffled per user, users get their own order from `/last_event`.
+        shuffled: bool,
+        /// Unix time in milliseconds at which the question is locked.
+        deadline: Option<u64>,
+    },
+    /// Players wager part of their score before the question is revealed.
+    Wager {
+        id: usize,
+        total: usize,
+        round: Option<RoundInfo>,
+    },
+    /// Who has the turn to answer a buzzer question.
+    Buzzer {
+        turn: Option<String>,
+        /// Users in the order they buzzed.
+        queue: Vec<String>,
+        /// The user whose answer was accepted, the question is over then.
+        accepted: Option<String>,
     },
     Ranking(Ranking),
+    Distribution(Distribution),
     Finished,
     Closed,
 }
 
-impl Event {
-    pub fn to_string(&self) -> String {
-        serde_json::to_string(self).unwrap()
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str(&serde_json::to_string(self).unwrap())
     }
-} 
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundInfo {
+    pub id: usize,
+    pub total: usize,
+    pub name: String,
+    /// Only sent with the first question of the round.
+    pub intro: Option<String>,
+}
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Score{
     Ungraded,
-    Grade(usize)
+    Grade(f64)
 }
 
 impl Score {
     pub fn is_ungraded(&self) -> bool {
-        match self {
-            Score::Ungraded => true,
-            _ => false
-        }
+        matches!(self, Score::Ungraded)
     }
 }
 
-impl From<Option<usize>> for Score {
-    fn from(opt: Option<usize>) -> Self {
+impl From<Option<f64>> for Score {
+    fn from(opt: Option<f64>) -> Self {
         match opt {
             Some(s) => Score::Grade(s),
             None => Score::Ungraded
@@ -52,13 +84,70 @@ impl From<Option<usize>> for Score {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ranking {
-    pub max_score: usize,
-    pub scores: Vec<(String,i32)>
+    pub max_score: f64,
+    pub scores: Vec<(String,f64)>,
+    /// Standings of the rounds that have started, without bonus scores.
+    #[serde(default)]
+    pub rounds: Vec<RoundRanking>,
+    /// Team standings, if players play in teams.
+    #[serde(default)]
+    pub teams: Vec<(String,f64)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundRanking {
+    pub name: String,
+    pub max_score: f64,
+    pub scores: Vec<(String,f64)>,
+}
+
+/// Answers and scores of all users on all questions, to export after a quiz.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Results {
+    pub title: String,
+    pub max_score: f64,
+    pub questions: Vec<QuestionResult>,
+    /// Users by rank, users with the same total share a rank.
+    pub users: Vec<UserResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionResult {
+    pub id: String,
+    pub title: String,
+    pub max_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserResult {
+    pub rank: usize,
+    pub user: String,
+    pub team: Option<String>,
+    /// Answer to every question, in the order of the questions.
+    pub answers: Vec<Option<AnswerResult>>,
+    pub bonus: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerResult {
+    pub answer: String,
+    pub grade: Score,
+}
+
+/// How many players chose each option of a choice question.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Distribution {
+    pub title: String,
+    pub counts: Vec<(String,usize)>
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QuizStatus {
     Lobby,
+    Wager{
+        id:usize
+    },
     Question{
         id:usize,
         locked:bool
@@ -68,14 +157,18 @@ pub enum QuizStatus {
 
 impl QuizStatus {
     pub fn is_lobby(&self) -> bool {
-        if let QuizStatus::Lobby = self { true } else { false }
+        matches!(self, QuizStatus::Lobby)
     }
     
+    pub fn is_wager(&self) -> bool {
+        matches!(self, QuizStatus::Wager{..})
+    }
+
     pub fn question(&self) -> Option<usize> {
         if let QuizStatus::Question{id,..} = self { Some(*id) } else { None }
     }
 
     pub fn _is_done(&self) -> bool {
-        if let QuizStatus::Done = self { true } else { false }
+        matches!(self, QuizStatus::Done)
     }
 }
\ No newline at end of file